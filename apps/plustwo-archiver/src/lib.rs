@@ -0,0 +1,45 @@
+//! Archives broadcasters' VODs as completed broadcasts.
+
+use plustwo_classifier::CommentRows;
+use plustwo_database::{
+    DatabaseError, DateTime, Storage as _, StorageTransaction as _, Transactional,
+};
+
+/// A VOD's broadcast, as it's stored once archived.
+pub struct ArchivedBroadcast<'a> {
+    pub broadcast_id: i64,
+    pub broadcaster_id: i64,
+    pub title: &'a str,
+    pub started_at: DateTime,
+    pub ended_at: DateTime,
+}
+
+/// Imports a VOD as a completed broadcast all at once, so a crash can't leave it ended but half
+/// imported.
+pub async fn store_broadcast(
+    db: &impl Transactional,
+    broadcast: &ArchivedBroadcast<'_>,
+    rows: &CommentRows,
+) -> Result<(), DatabaseError> {
+    let tx = db.begin().await?;
+    tx.start_broadcast(
+        broadcast.broadcast_id,
+        broadcast.broadcaster_id,
+        broadcast.title.to_string(),
+        broadcast.started_at,
+    )
+    .await?;
+    tx.insert_many_chatters(&rows.chatters).await?;
+    tx.insert_many_messages(&rows.messages).await?;
+    tx.rebuild_minute_stats(broadcast.broadcast_id).await?;
+    tx.insert_many_message_contents(&rows.contents).await?;
+    tx.insert_many_message_roles(&rows.roles).await?;
+    tx.end_broadcast(
+        broadcast.broadcaster_id,
+        broadcast.ended_at,
+        Some(broadcast.broadcast_id),
+    )
+    .await?;
+
+    tx.commit().await
+}
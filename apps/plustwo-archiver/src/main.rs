@@ -1,14 +1,13 @@
-use std::{collections::HashMap, time::Duration};
+use std::time::Duration;
 
 use eyre::Context;
 use indicatif::{ProgressBar, ProgressStyle};
-use plustwo_database::{
-    DatabaseClient,
-    entities::{self, sea_orm_active_enums::MessageKind},
-};
+use plustwo_archiver::{ArchivedBroadcast, store_broadcast};
+use plustwo_classifier::{CommentRows, Cooldowns, Exclusions, OptOuts, Vocabulary};
+use plustwo_database::{DatabaseClient, Transactional, entities};
 use plustwo_twitch_gql::{
-    CommentsByVideoAndCursorComment, CommentsByVideoAndCursorMessage, TwitchGqlClient,
-    collect_from_cursor, shared::video::TwitchVideo,
+    CommentsByVideoAndCursorComment, TwitchGqlClient, collect_from_cursor,
+    shared::video::TwitchVideo,
 };
 
 macro_rules! env_var {
@@ -28,10 +27,22 @@ async fn main() -> eyre::Result<()> {
 
     db.insert_broadcaster(
         broadcaster.id.parse()?,
-        &broadcaster_name,
+        &broadcaster.login,
+        &broadcaster.display_name,
         &broadcaster.profile_image_url,
     )
     .await?;
+    let settings = db
+        .get_broadcaster(broadcaster.id.parse()?)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Failed to find broadcaster {broadcaster_name} after insert"))?;
+
+    let mut vote_patterns_version = db.get_table_version("vote_patterns").await?;
+    let mut vocabulary = Vocabulary::from_patterns(&db.select_vote_patterns().await?)?;
+    let mut exclusions_version = db.get_table_version("excluded_chatters").await?;
+    let mut exclusions = Exclusions::from_models(&db.select_excluded_chatters().await?);
+    let mut opt_outs_version = db.get_table_version("opted_out_chatters").await?;
+    let mut opt_outs = OptOuts::from_models(&db.select_opted_out_chatters().await?);
 
     let currently_live_video = broadcaster.stream.map(|stream| stream.archive_video.id);
 
@@ -61,89 +72,108 @@ async fn main() -> eyre::Result<()> {
             continue;
         }
 
-        db.start_broadcast(
-            video.id.parse()?,
-            broadcaster.id.parse()?,
-            video.title.clone(),
-            video.created_at.naive_utc(),
-        )
-        .await?;
+        // Archiving can take a long time, so pick up any vote patterns, exclusions or opt-outs
+        // changed in the meantime.
+        let version = db.get_table_version("vote_patterns").await?;
+        if version != vote_patterns_version {
+            vocabulary = Vocabulary::from_patterns(&db.select_vote_patterns().await?)?;
+            vote_patterns_version = version;
+        }
 
-        let comment_bar =
-            ProgressBar::new(video.length_seconds).with_style(ProgressStyle::with_template(
-                "[{elapsed_precise}] {msg} {wide_bar} {pos}/{len} seconds ({eta})",
-            )?);
-        comment_bar.set_message(video.title);
-
-        let mut chatters = HashMap::new();
-        let mut messages = Vec::new();
-
-        for comment in collect_from_cursor(
-            async |cursor, _length, comments: &Vec<CommentsByVideoAndCursorComment>| {
-                if let Some(comment) = comments.last() {
-                    comment_bar
-                        .set_position((comment.created_at - video.created_at).num_seconds() as u64);
-                }
-
-                client
-                    .get_comments_by_video_and_cursor(&video.id, cursor)
-                    .await
-            },
-        )
-        .await?
-        {
-            // If the user cannot be parsed, skip the comment.
-            let Some(user) = comment.commenter else {
-                continue;
-            };
-            // If the comment is not a +2 or -2, skip it.
-            let Some(message_kind) = kind_from_message(&comment.message) else {
-                continue;
-            };
-
-            let chatter = entities::chatters::Model {
-                id: user.id.parse()?,
-                display_name: user.display_name,
-            };
-
-            chatters.insert(chatter.id, chatter.clone());
-            messages.push(entities::messages::Model {
-                id: comment.id,
-                broadcast_id: video.id.parse()?,
-                chatter_id: chatter.id,
-                sent_at: comment.created_at.naive_utc(),
-                message_kind,
-            });
+        let version = db.get_table_version("excluded_chatters").await?;
+        if version != exclusions_version {
+            exclusions = Exclusions::from_models(&db.select_excluded_chatters().await?);
+            exclusions_version = version;
         }
 
-        db.insert_many_chatters(chatters.values()).await?;
-        db.insert_many_messages(&messages).await?;
+        let version = db.get_table_version("opted_out_chatters").await?;
+        if version != opt_outs_version {
+            opt_outs = OptOuts::from_models(&db.select_opted_out_chatters().await?);
+            opt_outs_version = version;
+        }
 
-        db.end_broadcast(
-            broadcaster.id.parse()?,
-            (video.created_at + Duration::from_secs(video.length_seconds)).naive_utc(),
-            Some(video.id.parse()?),
+        archive_video(
+            &client,
+            &db,
+            &settings,
+            video,
+            &vocabulary,
+            &exclusions,
+            &opt_outs,
         )
         .await?;
-
-        comment_bar.finish();
         video_bar.inc(1);
     }
 
     Ok(())
 }
 
-fn kind_from_message(message: &CommentsByVideoAndCursorMessage) -> Option<MessageKind> {
-    let first_frag = &message.fragments.first()?.text;
-    let last_frag = &message.fragments.last()?.text;
-
-    Some(
-        if first_frag.starts_with("+2") || last_frag.ends_with("+2") {
-            MessageKind::PlusTwo
-        } else if first_frag.starts_with("-2") || last_frag.ends_with("-2") {
-            MessageKind::MinusTwo
-        } else {
-            return None;
+/// Imports every comment from a single VOD as a completed broadcast.
+async fn archive_video(
+    client: &TwitchGqlClient,
+    db: &impl Transactional,
+    broadcaster: &entities::broadcasters::Model,
+    video: TwitchVideo,
+    vocabulary: &Vocabulary,
+    exclusions: &Exclusions,
+    opt_outs: &OptOuts,
+) -> eyre::Result<()> {
+    let broadcast_id = video.id.parse()?;
+    let rows = CommentRows::from_comments(
+        fetch_comments(client, &video).await?,
+        broadcaster,
+        broadcast_id,
+        vocabulary,
+        exclusions,
+        opt_outs,
+        &mut Cooldowns::new(broadcaster.vote_cooldown_seconds),
+    )?;
+
+    store_broadcast(
+        db,
+        &ArchivedBroadcast {
+            broadcast_id,
+            broadcaster_id: broadcaster.id,
+            title: &video.title,
+            started_at: video.created_at.naive_utc(),
+            ended_at: (video.created_at + Duration::from_secs(video.length_seconds)).naive_utc(),
         },
+        &rows,
     )
+    .await?;
+
+    Ok(())
+}
+
+/// Fetches every comment from a VOD, showing progress through the VOD as they're fetched.
+async fn fetch_comments(
+    client: &TwitchGqlClient,
+    video: &TwitchVideo,
+) -> eyre::Result<Vec<CommentsByVideoAndCursorComment>> {
+    let comment_bar =
+        ProgressBar::new(video.length_seconds).with_style(ProgressStyle::with_template(
+            "[{elapsed_precise}] {msg} {wide_bar} {pos}/{len} seconds ({eta})",
+        )?);
+    comment_bar.set_message(video.title.clone());
+
+    let comments = collect_from_cursor(
+        async |cursor, _length, comments: &Vec<CommentsByVideoAndCursorComment>| {
+            if let Some(comment) = comments.last() {
+                comment_bar.set_position(
+                    (comment.created_at - video.created_at)
+                        .num_seconds()
+                        .unsigned_abs(),
+                );
+            }
+
+            client
+                .get_comments_by_video_and_cursor(&video.id, cursor)
+                .await
+        },
+    )
+    .await?;
+
+    comment_bar.finish();
+
+    Ok(comments)
 }
@@ -0,0 +1,104 @@
+//! Checks that archived broadcasts are stored all at once.
+
+use chrono::{NaiveDate, TimeDelta};
+use migration::{Migrator, MigratorTrait as _};
+use plustwo_archiver::{ArchivedBroadcast, store_broadcast};
+use plustwo_classifier::CommentRows;
+use plustwo_database::{
+    DatabaseClient, DateTime, MemoryStorage, Transactional, Uuid,
+    entities::{chatters, message_roles, messages, sea_orm_active_enums::MessageKind},
+};
+use sea_orm::Database;
+
+const BROADCASTER: i64 = 1;
+const BROADCAST: i64 = 101;
+const CHATTER: i64 = 201;
+
+fn started_at() -> DateTime {
+    NaiveDate::from_ymd_opt(2025, 4, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .expect("fixture time should be valid")
+}
+
+fn rows() -> CommentRows {
+    let id = Uuid::from_u128(1);
+    let sent_at = started_at() + TimeDelta::seconds(5);
+
+    CommentRows {
+        chatters: vec![(
+            chatters::Model {
+                id: CHATTER,
+                display_name: "Chatter".into(),
+            },
+            sent_at,
+        )],
+        messages: vec![messages::Model {
+            id,
+            broadcast_id: BROADCAST,
+            chatter_id: CHATTER,
+            sent_at,
+            message_kind: MessageKind::PlusTwo,
+            target_chatter_id: None,
+            value: 2,
+            suppressed: false,
+        }],
+        contents: Vec::new(),
+        roles: vec![message_roles::Model {
+            id,
+            is_subscriber: false,
+            is_moderator: false,
+            is_vip: false,
+            is_founder: false,
+            subscriber_months: None,
+        }],
+    }
+}
+
+async fn archive(db: &impl Transactional) {
+    let ended_at = started_at() + TimeDelta::hours(1);
+    db.insert_broadcaster(BROADCASTER, "archived", "Archived", "")
+        .await
+        .unwrap();
+
+    store_broadcast(
+        db,
+        &ArchivedBroadcast {
+            broadcast_id: BROADCAST,
+            broadcaster_id: BROADCASTER,
+            title: "archived",
+            started_at: started_at(),
+            ended_at,
+        },
+        &rows(),
+    )
+    .await
+    .unwrap();
+
+    let broadcast = db.get_broadcast(BROADCAST).await.unwrap().unwrap();
+    assert_eq!(broadcast.ended_at, Some(ended_at));
+    assert_eq!(
+        db.select_messages(BROADCAST).await.unwrap(),
+        rows().messages
+    );
+    assert_eq!(
+        db.select_minute_stats(BROADCAST).await.unwrap()[0].plus_count,
+        1
+    );
+}
+
+#[tokio::test]
+async fn broadcasts_are_stored_in_memory() {
+    archive(&MemoryStorage::new()).await;
+}
+
+#[tokio::test]
+async fn broadcasts_are_stored_in_a_database() {
+    let db = Database::connect("sqlite::memory:")
+        .await
+        .expect("database should open");
+    Migrator::up(&db, None)
+        .await
+        .expect("migrations should apply");
+
+    archive(&DatabaseClient::from(db)).await;
+}
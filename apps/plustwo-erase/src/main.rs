@@ -0,0 +1,42 @@
+use chrono::Utc;
+use eyre::Context;
+use plustwo_database::{DatabaseClient, Erasure};
+
+macro_rules! env_var {
+    ($name:expr) => {
+        ::std::env::var($name)
+            .wrap_err_with(|| format!("Failed to find environment variable {}", $name))?
+    };
+}
+
+/// Forgets a chatter who has asked for their data to be removed.
+///
+/// Reads `CHATTER_ID` and `ERASURE`, which is either `delete` to delete the chatter with all of
+/// their votes, or `anonymize` to keep their votes under an anonymous placeholder. `REASON` is
+/// recorded in the audit log alongside the erasure if set. Erased chatters are opted out, so
+/// they aren't recorded again.
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let db = DatabaseClient::new(&env_var!("DATABASE_URL")).await?;
+
+    let chatter_id = env_var!("CHATTER_ID")
+        .parse()
+        .wrap_err("Failed to parse CHATTER_ID")?;
+    let erasure = match env_var!("ERASURE").as_str() {
+        "delete" => Erasure::Delete,
+        "anonymize" => Erasure::Anonymize,
+        erasure => eyre::bail!("Unknown ERASURE {erasure}, expected delete or anonymize"),
+    };
+    let reason = std::env::var("REASON").ok().filter(|r| !r.is_empty());
+
+    let logged = db
+        .erase_chatter(chatter_id, erasure, reason, Utc::now().naive_utc())
+        .await?;
+
+    println!(
+        "Erased chatter {chatter_id} ({}): {} votes",
+        logged.action, logged.votes
+    );
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! Re-runs vote detection over stored message text, working out how stored votes need to change.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use plustwo_classifier::{Chatter, Cooldowns, Exclusions, NormalizedMessage, OptOuts, Vocabulary};
+use plustwo_database::{
+    DateTime, Uuid,
+    entities::{self, sea_orm_active_enums::MessageKind},
+};
+
+/// The changes needed to bring a broadcast's votes in line with a reclassification.
+pub struct Changes {
+    /// Chatters targeted by reclassified votes, seen when they were targeted.
+    pub targets: Vec<(entities::chatters::Model, DateTime)>,
+    pub inserted: Vec<entities::messages::Model>,
+    pub updated: Vec<entities::messages::Model>,
+    /// Votes which aren't votes anymore. Their stored text is deleted along with them.
+    pub deleted: Vec<Uuid>,
+}
+
+/// Reclassifies the stored text of a broadcast's messages against its `existing` votes,
+/// recording every change of kind in the summary.
+///
+/// Opted out chatters' messages are skipped, and they're never targeted.
+#[allow(clippy::too_many_arguments)]
+pub fn reclassify(
+    vocabulary: &Vocabulary,
+    exclusions: &Exclusions,
+    opt_outs: &OptOuts,
+    broadcaster_id: i64,
+    cooldown_seconds: Option<i32>,
+    contents: Vec<entities::message_contents::Model>,
+    existing: Vec<entities::messages::Model>,
+    summary: &mut Summary,
+) -> eyre::Result<Changes> {
+    let mut existing: HashMap<_, _> = existing.into_iter().map(|m| (m.id, m)).collect();
+    let mut targets = Vec::new();
+    let mut reclassified = HashSet::new();
+    let mut votes = Vec::new();
+
+    for content in contents
+        .into_iter()
+        .filter(|c| !opt_outs.is_opted_out(c.chatter_id))
+    {
+        let reply_to = content
+            .reply_to_chatter_id
+            .zip(content.reply_to_display_name)
+            .map(|(id, display_name)| Chatter {
+                id: id.to_string(),
+                display_name,
+            });
+        let message = NormalizedMessage {
+            chatter_id: content.chatter_id.to_string(),
+            text: content.text,
+            fragments: serde_json::from_value(content.fragments)?,
+            reply_to,
+        };
+        let classification =
+            plustwo_classifier::classify_with(vocabulary, broadcaster_id, &message)
+                .filter(|_| !exclusions.is_excluded(broadcaster_id, content.chatter_id));
+
+        reclassified.insert(content.id);
+        summary.record(
+            existing.get(&content.id).map(|m| &m.message_kind),
+            classification.as_ref().map(|c| &c.kind),
+        );
+
+        let Some(classification) = classification else {
+            continue;
+        };
+        let target = classification
+            .target
+            .map(|t| entities::chatters::Model::try_from(t.chatter))
+            .transpose()?
+            .filter(|t| !opt_outs.is_opted_out(t.id));
+        // Text stored before replies were kept loses its reply, so the stored target is kept
+        // rather than dropped.
+        let target_chatter_id = target.as_ref().map(|t| t.id).or_else(|| {
+            existing
+                .get(&content.id)
+                .and_then(|m| m.target_chatter_id)
+                .filter(|&id| !opt_outs.is_opted_out(id))
+        });
+
+        votes.push(entities::messages::Model {
+            id: content.id,
+            broadcast_id: content.broadcast_id,
+            chatter_id: content.chatter_id,
+            sent_at: content.sent_at,
+            message_kind: classification.kind,
+            target_chatter_id,
+            value: classification.value,
+            suppressed: false,
+        });
+
+        if let Some(target) = target {
+            targets.push((target, content.sent_at));
+        }
+    }
+
+    // Votes without stored text or from opted out chatters keep their kind, but still count towards cooldowns.
+    votes.extend(
+        existing
+            .values()
+            .filter(|m| !reclassified.contains(&m.id))
+            .cloned(),
+    );
+    votes.sort_by_key(|m| m.sent_at);
+
+    let mut cooldowns = Cooldowns::new(cooldown_seconds);
+    let mut inserted = Vec::new();
+    let mut updated = Vec::new();
+
+    for mut vote in votes {
+        vote.suppressed = cooldowns.suppress(vote.chatter_id, vote.sent_at);
+
+        match existing.remove(&vote.id) {
+            None => inserted.push(vote),
+            Some(old)
+                if (
+                    &old.message_kind,
+                    old.target_chatter_id,
+                    old.value,
+                    old.suppressed,
+                ) != (
+                    &vote.message_kind,
+                    vote.target_chatter_id,
+                    vote.value,
+                    vote.suppressed,
+                ) =>
+            {
+                updated.push(vote);
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(Changes {
+        targets,
+        inserted,
+        updated,
+        // Anything left over was a vote, but isn't anymore.
+        deleted: existing.into_keys().collect(),
+    })
+}
+
+/// Counts how many messages moved between each pair of kinds.
+#[derive(Default)]
+pub struct Summary {
+    transitions: BTreeMap<(String, String), usize>,
+}
+impl Summary {
+    pub fn record(&mut self, old: Option<&MessageKind>, new: Option<&MessageKind>) {
+        *self
+            .transitions
+            .entry((kind_name(old), kind_name(new)))
+            .or_default() += 1;
+    }
+
+    pub fn print(&self, dry_run: bool) {
+        println!();
+        println!(
+            "{}",
+            if dry_run {
+                "Summary (dry run, nothing was written):"
+            } else {
+                "Summary:"
+            }
+        );
+
+        for ((old, new), count) in &self.transitions {
+            let marker = if old == new { " " } else { "*" };
+            println!("{marker} {old:>10} -> {new:<10} {count}");
+        }
+    }
+}
+
+fn kind_name(kind: Option<&MessageKind>) -> String {
+    kind.map_or_else(|| "none".to_string(), |k| format!("{k:?}"))
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use eyre::Context;
+use plustwo_classifier::{Exclusions, OptOuts, Vocabulary};
+use plustwo_database::{DatabaseClient, DateTime};
+use plustwo_reclassifier::{Changes, Summary, reclassify};
+
+macro_rules! env_var {
+    ($name:expr) => {
+        ::std::env::var($name)
+            .wrap_err_with(|| format!("Failed to find environment variable {}", $name))?
+    };
+}
+
+/// Re-runs vote detection over every vote with stored text, updating the stored votes to
+/// match.
+///
+/// Reads `BROADCASTER_ID`, `FROM` and `TO` (dates formatted as `YYYY-MM-DD`, `TO` inclusive) to
+/// narrow down which messages are reclassified. If `DRY_RUN` is set, only the summary is printed.
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let db = DatabaseClient::new(&env_var!("DATABASE_URL")).await?;
+
+    let broadcaster_id = optional_env_var("BROADCASTER_ID")
+        .map(|id| id.parse())
+        .transpose()
+        .wrap_err("Failed to parse BROADCASTER_ID")?;
+    let from = optional_env_var("FROM")
+        .map(|d| parse_date(&d))
+        .transpose()?;
+    let to = optional_env_var("TO")
+        .map(|d| parse_date(&d).map(|d| d + chrono::Duration::days(1)))
+        .transpose()?;
+    let dry_run = optional_env_var("DRY_RUN").is_some();
+
+    let vocabulary = Vocabulary::from_patterns(&db.select_vote_patterns().await?)?;
+    let exclusions = Exclusions::from_models(&db.select_excluded_chatters().await?);
+    let opt_outs = OptOuts::from_models(&db.select_opted_out_chatters().await?);
+    let broadcasters: HashMap<_, _> = db
+        .select_broadcasters()
+        .await?
+        .into_iter()
+        .map(|b| (b.id, b))
+        .collect();
+
+    let mut summary = Summary::default();
+
+    for broadcast in db.select_broadcasts(broadcaster_id, from, to).await? {
+        let contents = db.select_message_contents(broadcast.id, from, to).await?;
+        if contents.is_empty() {
+            continue;
+        }
+
+        let existing = db.select_messages(broadcast.id).await?;
+        let cooldown_seconds = broadcasters
+            .get(&broadcast.broadcaster_id)
+            .and_then(|b| b.vote_cooldown_seconds);
+
+        let Changes {
+            targets,
+            inserted,
+            updated,
+            deleted,
+        } = reclassify(
+            &vocabulary,
+            &exclusions,
+            &opt_outs,
+            broadcast.broadcaster_id,
+            cooldown_seconds,
+            contents,
+            existing,
+            &mut summary,
+        )?;
+
+        println!(
+            "{} ({}): {} added, {} changed, {} removed",
+            broadcast.title,
+            broadcast.id,
+            inserted.len(),
+            updated.len(),
+            deleted.len()
+        );
+
+        if dry_run {
+            continue;
+        }
+
+        let tx = db.begin().await?;
+        tx.insert_many_chatters(&targets).await?;
+        tx.insert_many_messages(&inserted).await?;
+        for vote in updated {
+            tx.update_message_vote(
+                vote.id,
+                vote.message_kind,
+                vote.target_chatter_id,
+                vote.value,
+                vote.suppressed,
+            )
+            .await?;
+        }
+        tx.delete_many_messages(&deleted).await?;
+        tx.rebuild_minute_stats(broadcast.id).await?;
+        tx.commit().await?;
+    }
+
+    summary.print(dry_run);
+
+    Ok(())
+}
+
+fn optional_env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parse_date(date: &str) -> eyre::Result<DateTime> {
+    Ok(NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .wrap_err_with(|| format!("Failed to parse date {date}"))?
+        .and_time(chrono::NaiveTime::MIN))
+}
@@ -0,0 +1,181 @@
+use chrono::NaiveDate;
+use plustwo_classifier::{Chatter, Exclusions, Fragment, OptOuts, Vocabulary};
+use plustwo_database::{
+    DateTime, Uuid,
+    entities::{message_contents, messages, opted_out_chatters, sea_orm_active_enums::MessageKind},
+};
+use plustwo_reclassifier::{Summary, reclassify};
+
+const BROADCASTER: i64 = 1;
+const BROADCAST: i64 = 101;
+const SENDER: i64 = 201;
+const TARGET: i64 = 202;
+
+fn sent_at() -> DateTime {
+    NaiveDate::from_ymd_opt(2025, 4, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .expect("fixture time should be valid")
+}
+
+fn content(fragments: &[Fragment]) -> message_contents::Model {
+    message_contents::Model {
+        id: Uuid::from_u128(1),
+        broadcast_id: BROADCAST,
+        chatter_id: SENDER,
+        sent_at: sent_at(),
+        text: fragments.iter().map(Fragment::as_str).collect(),
+        fragments: serde_json::to_value(fragments).expect("fragments should serialize"),
+        reply_to_chatter_id: None,
+        reply_to_display_name: None,
+    }
+}
+
+fn vote(target_chatter_id: Option<i64>) -> messages::Model {
+    messages::Model {
+        id: Uuid::from_u128(1),
+        broadcast_id: BROADCAST,
+        chatter_id: SENDER,
+        sent_at: sent_at(),
+        message_kind: MessageKind::PlusTwo,
+        target_chatter_id,
+        value: 2,
+        suppressed: false,
+    }
+}
+
+fn targeted() -> [Fragment; 2] {
+    [
+        Fragment::text("+2 "),
+        Fragment::Mention {
+            text: "@Bob".into(),
+            chatter: Chatter {
+                id: TARGET.to_string(),
+                display_name: "Bob".into(),
+            },
+        },
+    ]
+}
+
+fn opt_out(chatter_id: i64) -> opted_out_chatters::Model {
+    opted_out_chatters::Model {
+        chatter_id,
+        reason: None,
+        opted_out_at: sent_at(),
+    }
+}
+
+#[test]
+fn changed_targets_are_updated() {
+    let changes = reclassify(
+        &Vocabulary::default(),
+        &Exclusions::default(),
+        &OptOuts::default(),
+        BROADCASTER,
+        None,
+        vec![content(&targeted())],
+        vec![vote(None)],
+        &mut Summary::default(),
+    )
+    .unwrap();
+
+    assert!(changes.inserted.is_empty());
+    assert!(changes.deleted.is_empty());
+    assert_eq!(changes.updated, [vote(Some(TARGET))]);
+    assert_eq!(changes.targets.len(), 1);
+}
+
+#[test]
+fn replies_keep_their_target() {
+    let reply = message_contents::Model {
+        reply_to_chatter_id: Some(TARGET),
+        reply_to_display_name: Some("Bob".into()),
+        ..content(&[Fragment::text("+2")])
+    };
+    let changes = reclassify(
+        &Vocabulary::default(),
+        &Exclusions::default(),
+        &OptOuts::default(),
+        BROADCASTER,
+        None,
+        vec![reply],
+        vec![vote(Some(TARGET))],
+        &mut Summary::default(),
+    )
+    .unwrap();
+
+    assert!(changes.inserted.is_empty());
+    assert!(changes.updated.is_empty());
+    assert!(changes.deleted.is_empty());
+    assert_eq!(changes.targets.len(), 1);
+}
+
+#[test]
+fn stored_targets_are_kept_when_none_are_found() {
+    // Text stored without its reply can't find the target again.
+    let changes = reclassify(
+        &Vocabulary::default(),
+        &Exclusions::default(),
+        &OptOuts::default(),
+        BROADCASTER,
+        None,
+        vec![content(&[Fragment::text("+2")])],
+        vec![vote(Some(TARGET))],
+        &mut Summary::default(),
+    )
+    .unwrap();
+
+    assert!(changes.updated.is_empty());
+    assert!(changes.deleted.is_empty());
+}
+
+#[test]
+fn unchanged_votes_are_left_alone() {
+    let changes = reclassify(
+        &Vocabulary::default(),
+        &Exclusions::default(),
+        &OptOuts::default(),
+        BROADCASTER,
+        None,
+        vec![content(&[Fragment::text("+2")])],
+        vec![vote(None)],
+        &mut Summary::default(),
+    )
+    .unwrap();
+
+    assert!(changes.inserted.is_empty());
+    assert!(changes.updated.is_empty());
+    assert!(changes.deleted.is_empty());
+}
+
+#[test]
+fn opted_out_chatters_are_skipped() {
+    // Opted out targets are dropped, like in the watcher.
+    let changes = reclassify(
+        &Vocabulary::default(),
+        &Exclusions::default(),
+        &OptOuts::from_models(&[opt_out(TARGET)]),
+        BROADCASTER,
+        None,
+        vec![content(&targeted())],
+        Vec::new(),
+        &mut Summary::default(),
+    )
+    .unwrap();
+    assert_eq!(changes.inserted, [vote(None)]);
+    assert!(changes.targets.is_empty());
+
+    // Opted out senders' messages aren't turned into votes.
+    let changes = reclassify(
+        &Vocabulary::default(),
+        &Exclusions::default(),
+        &OptOuts::from_models(&[opt_out(SENDER)]),
+        BROADCASTER,
+        None,
+        vec![content(&targeted())],
+        Vec::new(),
+        &mut Summary::default(),
+    )
+    .unwrap();
+    assert!(changes.inserted.is_empty());
+    assert!(changes.targets.is_empty());
+}
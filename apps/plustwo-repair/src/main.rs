@@ -0,0 +1,69 @@
+use chrono::NaiveDate;
+use eyre::Context;
+use plustwo_database::{DatabaseClient, DateTime};
+
+macro_rules! env_var {
+    ($name:expr) => {
+        ::std::env::var($name)
+            .wrap_err_with(|| format!("Failed to find environment variable {}", $name))?
+    };
+}
+
+/// Recomputes the per-minute rollups of broadcasts from their stored votes.
+///
+/// Reads `BROADCAST_ID` to repair a single broadcast. Otherwise, reads `BROADCASTER_ID`, `FROM`
+/// and `TO` (dates formatted as `YYYY-MM-DD`, `TO` inclusive) to narrow down which broadcasts are
+/// repaired, repairing every broadcast if none are set.
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let db = DatabaseClient::new(&env_var!("DATABASE_URL")).await?;
+
+    let broadcast_id = optional_env_var("BROADCAST_ID")
+        .map(|id| id.parse())
+        .transpose()
+        .wrap_err("Failed to parse BROADCAST_ID")?;
+    let broadcaster_id = optional_env_var("BROADCASTER_ID")
+        .map(|id| id.parse())
+        .transpose()
+        .wrap_err("Failed to parse BROADCASTER_ID")?;
+    let from = optional_env_var("FROM")
+        .map(|d| parse_date(&d))
+        .transpose()?;
+    let to = optional_env_var("TO")
+        .map(|d| parse_date(&d).map(|d| d + chrono::Duration::days(1)))
+        .transpose()?;
+
+    let broadcasts = if let Some(broadcast_id) = broadcast_id {
+        let broadcast = db
+            .get_broadcast(broadcast_id)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Failed to find broadcast {broadcast_id}"))?;
+
+        vec![broadcast]
+    } else {
+        db.select_broadcasts(broadcaster_id, from, to).await?
+    };
+
+    for broadcast in broadcasts {
+        // Each broadcast is rebuilt on its own, so a failure part way through keeps the
+        // broadcasts already repaired.
+        let tx = db.begin().await?;
+        tx.rebuild_minute_stats(broadcast.id).await?;
+        let minutes = tx.select_minute_stats(broadcast.id).await?.len();
+        tx.commit().await?;
+
+        println!("{} ({}): {minutes} minutes", broadcast.title, broadcast.id);
+    }
+
+    Ok(())
+}
+
+fn optional_env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parse_date(date: &str) -> eyre::Result<DateTime> {
+    Ok(NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .wrap_err_with(|| format!("Failed to parse date {date}"))?
+        .and_time(chrono::NaiveTime::MIN))
+}
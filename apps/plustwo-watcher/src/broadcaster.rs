@@ -1,7 +1,6 @@
-use std::collections::HashMap;
-
 use eyre::Result;
-use plustwo_database::DatabaseClient;
+use plustwo_classifier::{CommentRows, Cooldowns, Exclusions, OptOuts, Vocabulary};
+use plustwo_database::{Storage as _, StorageTransaction as _, Transactional};
 use plustwo_twitch_gql::{
     CommentsByVideoAndCursorComment, TwitchGqlClient, UserAndStreamByLoginStream,
     collect_from_cursor,
@@ -12,13 +11,15 @@ use twitch_api::eventsub::{
     stream::{StreamOfflineV1, StreamOnlineV1},
 };
 
-use crate::{kind_from_message, twitch::TwitchClient};
+use crate::twitch::TwitchClient;
 
 #[derive(Debug, Clone)]
 pub struct WatchedBroadcaster {
     pub broadcaster: plustwo_database::entities::broadcasters::Model,
     pub current_broadcast: Option<UserAndStreamByLoginStream>,
     pub is_watching: bool,
+    /// Shared between catchup and live messages, so both suppress the same votes.
+    pub cooldowns: Cooldowns,
 }
 impl WatchedBroadcaster {
     pub async fn watch(
@@ -66,72 +67,84 @@ impl WatchedBroadcaster {
         Ok(())
     }
 
-    pub async fn catchup(&self, db: &DatabaseClient, gql: &TwitchGqlClient) -> Result<()> {
+    /// Imports every comment sent so far in the current broadcast.
+    pub async fn catchup(
+        &mut self,
+        db: &impl Transactional,
+        gql: &TwitchGqlClient,
+        vocabulary: &Vocabulary,
+        exclusions: &Exclusions,
+        opt_outs: &OptOuts,
+    ) -> Result<()> {
         let Some(stream) = &self.current_broadcast else {
             return Ok(());
         };
 
-        db.start_broadcast(
-            stream.archive_video.id.parse()?,
-            self.broadcaster.id,
-            stream.archive_video.title.clone(),
-            stream.archive_video.created_at.naive_utc(),
-        )
+        let comments = collect_from_cursor(async |cursor, _, comments| {
+            tracing::debug!(
+                name = "CatchupProgress",
+                broadcaster = self.broadcaster.display_name,
+                comments = comments.len(),
+                cursor = cursor
+            );
+
+            gql.get_comments_by_video_and_cursor(&stream.archive_video.id, cursor)
+                .await
+        })
         .await?;
 
-        let mut chatter_map = HashMap::new();
-        let mut messages = Vec::new();
-
-        let comments: Vec<CommentsByVideoAndCursorComment> =
-            collect_from_cursor(async |cursor, _, comments| {
-                tracing::debug!(
-                    name = "CatchupProgress",
-                    broadcaster = self.broadcaster.display_name,
-                    comments = comments.len(),
-                    cursor = cursor
-                );
-
-                gql.get_comments_by_video_and_cursor(&stream.archive_video.id, cursor)
-                    .await
-            })
-            .await?;
-
-        for comment in comments {
-            // Some users don't show up. Maybe they've deleted their account or been
-            // banned?
-            let Some(user) = comment.commenter else {
-                continue;
-            };
-
-            let Some(message_kind) = kind_from_message(&comment.message) else {
-                continue;
-            };
-
-            let chatter = plustwo_database::entities::chatters::Model {
-                id: user.id.parse()?,
-                display_name: user.display_name,
-            };
-
-            chatter_map.insert(chatter.id, chatter.clone());
-            messages.push(plustwo_database::entities::messages::Model {
-                id: comment.id,
-                broadcast_id: stream.archive_video.id.parse()?,
-                chatter_id: chatter.id,
-                sent_at: comment.created_at.naive_utc(),
-                message_kind,
-            });
-        }
+        self.import(db, comments, vocabulary, exclusions, opt_outs)
+            .await
+    }
+
+    /// Imports comments from the current broadcast in a single transaction, along with the
+    /// broadcast itself.
+    pub async fn import(
+        &mut self,
+        db: &impl Transactional,
+        comments: Vec<CommentsByVideoAndCursorComment>,
+        vocabulary: &Vocabulary,
+        exclusions: &Exclusions,
+        opt_outs: &OptOuts,
+    ) -> Result<()> {
+        let Some(stream) = &self.current_broadcast else {
+            return Ok(());
+        };
+
+        let broadcast_id = stream.archive_video.id.parse()?;
+        let rows = CommentRows::from_comments(
+            comments,
+            &self.broadcaster,
+            broadcast_id,
+            vocabulary,
+            exclusions,
+            opt_outs,
+            &mut self.cooldowns,
+        )?;
 
         tracing::info!(
             name = "CatchupComplete",
             broadcaster = self.broadcaster.display_name,
-            chatters = chatter_map.len(),
-            messages = messages.len(),
+            sightings = rows.chatters.len(),
+            messages = rows.messages.len(),
         );
 
-        // Insert chatters and messages in a huge block to significantly increase performance.
-        db.insert_many_chatters(chatter_map.values()).await?;
-        db.insert_many_messages(&messages).await?;
+        // Insert chatters and messages in a huge block to significantly increase performance, and
+        // in a transaction so a crash can't leave the broadcast half imported.
+        let tx = db.begin().await?;
+        tx.start_broadcast(
+            broadcast_id,
+            self.broadcaster.id,
+            stream.archive_video.title.clone(),
+            stream.archive_video.created_at.naive_utc(),
+        )
+        .await?;
+        tx.insert_many_chatters(&rows.chatters).await?;
+        tx.insert_many_messages(&rows.messages).await?;
+        tx.rebuild_minute_stats(broadcast_id).await?;
+        tx.insert_many_message_contents(&rows.contents).await?;
+        tx.insert_many_message_roles(&rows.roles).await?;
+        tx.commit().await?;
 
         Ok(())
     }
@@ -0,0 +1,278 @@
+use eyre::{Context as _, Result, bail};
+use plustwo_classifier::{Chatter, Fragment, NormalizedMessage};
+use plustwo_database::{DatabaseError, DateTime, Storage, entities};
+use plustwo_twitch_gql::{TwitchGqlClient, shared::badge::ChatterRoles};
+use twitch_api::{
+    eventsub::{
+        channel::{ChannelChatMessageV1Payload, chat},
+        stream::{StreamOfflineV1Payload, StreamOnlineV1Payload},
+    },
+    types::Timestamp,
+};
+
+use crate::state::State;
+
+/// Starts recording the broadcaster's new broadcast.
+pub async fn on_stream_online(
+    graphql_client: &TwitchGqlClient,
+    db: &impl Storage,
+    payload: &StreamOnlineV1Payload,
+    state: &mut State,
+) -> Result<()> {
+    let Some(broadcaster) = state
+        .broadcasters
+        .get_mut(&payload.broadcaster_user_id.as_str().parse()?)
+    else {
+        tracing::warn!(
+            "Somehow managed to recv a StreamOnline for a broadcaster who wasn't tracked ({})",
+            payload.broadcaster_user_login
+        );
+        return Ok(());
+    };
+
+    let Some(stream) = graphql_client
+        .get_stream_by_id(payload.broadcaster_user_id.as_str())
+        .await?
+        .and_then(|user| user.stream)
+    else {
+        bail!("Failed to find broadcast after StreamOnline for {broadcaster:?}")
+    };
+
+    db.start_broadcast(
+        stream.archive_video.id.parse()?,
+        broadcaster.broadcaster.id,
+        stream.archive_video.title.clone(),
+        timestamp_to_time(&payload.started_at)?,
+    )
+    .await?;
+
+    broadcaster.current_broadcast = Some(stream);
+    broadcaster.cooldowns.clear();
+
+    Ok(())
+}
+
+/// Ends the broadcaster's current broadcast, if it was recorded.
+pub async fn on_stream_offline(
+    db: &impl Storage,
+    timestamp: &Timestamp,
+    payload: &StreamOfflineV1Payload,
+    state: &mut State,
+) -> Result<()> {
+    let Some(broadcaster) = state
+        .broadcasters
+        .get_mut(&payload.broadcaster_user_id.as_str().parse()?)
+    else {
+        tracing::warn!(
+            "Failed to find broadcaster after StreamOffline ({})",
+            payload.broadcaster_user_login
+        );
+        return Ok(());
+    };
+
+    let broadcast_id = broadcaster
+        .current_broadcast
+        .as_ref()
+        .map(|b| b.archive_video.id.parse())
+        .transpose()?;
+
+    match db
+        .end_broadcast(
+            broadcaster.broadcaster.id,
+            timestamp_to_time(timestamp)?,
+            broadcast_id,
+        )
+        .await
+    {
+        Ok(()) => {}
+        // The broadcast started before we were watching and was never recorded, so there's
+        // nothing to end.
+        Err(DatabaseError::BroadcastNotFound { .. }) => {
+            tracing::warn!(
+                "Failed to find broadcast to end after StreamOffline ({})",
+                payload.broadcaster_user_login
+            );
+        }
+        Err(error) => return Err(error.into()),
+    }
+
+    broadcaster.current_broadcast = None;
+
+    Ok(())
+}
+
+/// Records a chat message, along with its vote if it's one.
+pub async fn on_chat_message(
+    db: &impl Storage,
+    payload: &ChannelChatMessageV1Payload,
+    sent_at: twitch_api::types::Timestamp,
+    state: &mut State,
+) -> Result<()> {
+    let Some(broadcaster) = state
+        .broadcasters
+        .get_mut(&payload.broadcaster_user_id.as_str().parse()?)
+    else {
+        tracing::warn!(
+            "Somehow managed to recv a message for an untracked broadcaster ({})",
+            payload.broadcaster_user_login
+        );
+        return Ok(());
+    };
+
+    // Messages can be sent while a broadcaster isn't live, and we should skip
+    // these.
+    let Some(broadcast) = broadcaster.current_broadcast.as_ref() else {
+        return Ok(());
+    };
+
+    let chatter_id = payload.chatter_user_id.as_str().parse()?;
+    // Chatters who've opted out are never recorded, not even their roles or text.
+    if state.opt_outs.is_opted_out(chatter_id) {
+        return Ok(());
+    }
+
+    let message = message_from_payload(payload);
+    // Only votes are recorded, and votes from excluded chatters, such as bots, are ignored.
+    let Some(classification) =
+        plustwo_classifier::classify_with(&state.vocabulary, broadcaster.broadcaster.id, &message)
+            .filter(|_| {
+                !state
+                    .exclusions
+                    .is_excluded(broadcaster.broadcaster.id, chatter_id)
+            })
+    else {
+        return Ok(());
+    };
+
+    tracing::info!(
+        name: "ChatMessage",
+        broadcaster = payload.broadcaster_user_name.as_str(),
+        chatter = payload.chatter_user_name.as_str(),
+        value = classification.value,
+        rule = classification.explain(),
+    );
+
+    // Record the chatter, along with the name they're currently using.
+    let sent_at = timestamp_to_time(&sent_at)?;
+    db.insert_chatter(chatter_id, payload.chatter_user_name.to_string(), sent_at)
+        .await?;
+
+    // Votes for chatters who've opted out still count, without recording who they were for.
+    let target = classification
+        .target
+        .map(|t| entities::chatters::Model::try_from(t.chatter))
+        .transpose()?
+        .filter(|t| !state.opt_outs.is_opted_out(t.id));
+    if let Some(target) = &target {
+        db.insert_chatter(target.id, target.display_name.clone(), sent_at)
+            .await?;
+    }
+
+    let id = payload.message_id.as_str().parse()?;
+    let broadcast_id = broadcast.archive_video.id.parse()?;
+    db.insert_message(entities::messages::Model {
+        id,
+        broadcast_id,
+        chatter_id,
+        sent_at,
+        message_kind: classification.kind,
+        target_chatter_id: target.map(|t| t.id),
+        value: classification.value,
+        suppressed: broadcaster.cooldowns.suppress(chatter_id, sent_at),
+    })
+    .await?;
+    db.refresh_minute_stats(broadcast_id, sent_at).await?;
+
+    let roles = roles_from_badges(&payload.badges);
+    db.insert_message_roles(entities::message_roles::Model {
+        id,
+        is_subscriber: roles.subscriber,
+        is_moderator: roles.moderator,
+        is_vip: roles.vip,
+        is_founder: roles.founder,
+        subscriber_months: roles.subscriber_months,
+    })
+    .await?;
+
+    // Broadcasters who have opted in also have the text of their votes stored, so they can be
+    // reclassified later. The chatter being replied to is kept with it, since it isn't part of
+    // the text.
+    if broadcaster.broadcaster.store_message_text {
+        let reply_to = message
+            .reply_to
+            .map(entities::chatters::Model::try_from)
+            .transpose()?
+            .filter(|c| !state.opt_outs.is_opted_out(c.id));
+        db.insert_message_content(entities::message_contents::Model {
+            id,
+            broadcast_id,
+            chatter_id,
+            sent_at,
+            text: message.text,
+            fragments: serde_json::to_value(&message.fragments)?,
+            reply_to_chatter_id: reply_to.as_ref().map(|c| c.id),
+            reply_to_display_name: reply_to.map(|c| c.display_name),
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Converts a live chat message into the shape shared with VOD comments.
+fn message_from_payload(payload: &ChannelChatMessageV1Payload) -> NormalizedMessage {
+    NormalizedMessage {
+        chatter_id: payload.chatter_user_id.to_string(),
+        text: payload.message.text.clone(),
+        fragments: payload
+            .message
+            .fragments
+            .iter()
+            .map(|f| match f {
+                chat::Fragment::Emote { text, emote } => Fragment::Emote {
+                    text: text.clone(),
+                    emote_id: emote.id.to_string(),
+                },
+                chat::Fragment::Mention { text, mention } => Fragment::Mention {
+                    text: text.clone(),
+                    chatter: Chatter {
+                        id: mention.user_id.to_string(),
+                        display_name: mention.user_name.to_string(),
+                    },
+                },
+                chat::Fragment::Cheermote { text, cheermote } => Fragment::Cheermote {
+                    text: text.clone(),
+                    prefix: cheermote.prefix.clone(),
+                    bits: cheermote.bits,
+                },
+                // Plain text, or any kind of fragment added since.
+                _ => Fragment::text(f.text()),
+            })
+            .collect(),
+        reply_to: payload.reply.as_ref().map(|r| Chatter {
+            id: r.parent_user_id.to_string(),
+            display_name: r.parent_user_name.to_string(),
+        }),
+    }
+}
+
+fn roles_from_badges(badges: &[chat::message::Badge]) -> ChatterRoles {
+    let mut roles =
+        ChatterRoles::from_badges(badges.iter().map(|b| (b.set_id.as_str(), b.id.as_str())));
+
+    // Unlike VODs, EventSub includes the exact tenure in the subscriber badge's info.
+    if let Some(months) = badges
+        .iter()
+        .find(|b| b.set_id.as_str() == "subscriber")
+        .and_then(|b| b.info.parse().ok())
+    {
+        roles.subscriber_months = Some(months);
+    }
+
+    roles
+}
+
+fn timestamp_to_time(ts: &Timestamp) -> Result<DateTime> {
+    DateTime::parse_from_str(ts.as_str(), "%Y-%m-%dT%H:%M:%S%.f%Z")
+        .wrap_err("Failed to transform timestamp to datetime")
+}
@@ -0,0 +1,7 @@
+//! Watches broadcasters' chats over `EventSub`, recording votes as they're sent.
+
+pub mod broadcaster;
+pub mod handlers;
+pub mod socket;
+pub mod state;
+pub mod twitch;
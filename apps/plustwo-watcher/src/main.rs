@@ -1,24 +1,17 @@
 use eyre::{Context as _, Result, bail};
-use plustwo_database::{DatabaseClient, DateTime, entities::sea_orm_active_enums::MessageKind};
-use plustwo_twitch_gql::{CommentsByVideoAndCursorMessage, TwitchGqlClient};
-use socket::EventSubSocket;
-use state::State;
-use twitch::TwitchClient;
+use plustwo_database::DatabaseClient;
+use plustwo_twitch_gql::TwitchGqlClient;
+use plustwo_watcher::{
+    handlers::{on_chat_message, on_stream_offline, on_stream_online},
+    socket::EventSubSocket,
+    state::State,
+    twitch::TwitchClient,
+};
 use twitch_api::{
     TWITCH_EVENTSUB_WEBSOCKET_URL,
-    eventsub::{
-        Event as TwitchEvent, EventsubWebsocketData, Message, Payload,
-        channel::ChannelChatMessageV1Payload,
-        stream::{StreamOfflineV1Payload, StreamOnlineV1Payload},
-    },
-    types::Timestamp,
+    eventsub::{Event as TwitchEvent, EventsubWebsocketData, Message, Payload},
 };
 
-mod broadcaster;
-mod socket;
-mod state;
-mod twitch;
-
 macro_rules! env_var {
     ($name:expr) => {
         ::std::env::var($name)
@@ -41,7 +34,7 @@ async fn main() -> Result<()> {
     )
     .await?;
 
-    let mut state = State::new(&graphql_client, env_var!("TWITCH_USER")).await?;
+    let mut state = State::new(&db, &graphql_client, env_var!("TWITCH_USER")).await?;
 
     let mut eventsub = EventSubSocket::connect(TWITCH_EVENTSUB_WEBSOCKET_URL.as_str()).await?;
     loop {
@@ -55,6 +48,11 @@ async fn main() -> Result<()> {
                 .await?;
         }
 
+        // Reload vote patterns, exclusions and opt-outs if they've been changed.
+        if state.should_update_vote_settings() {
+            state.update_vote_settings(&db).await?;
+        }
+
         let msg = eventsub.next_message().await?;
         let event = twitch_api::eventsub::Event::parse_websocket(&msg)?;
 
@@ -126,7 +124,8 @@ async fn main() -> Result<()> {
                     message: Message::Notification(payload),
                     ..
                 }) => {
-                    on_chat_message(&db, &payload, metadata.message_timestamp.into(), &state).await
+                    on_chat_message(&db, &payload, metadata.message_timestamp.into(), &mut state)
+                        .await
                 }
 
                 ev => {
@@ -142,148 +141,3 @@ async fn main() -> Result<()> {
         }?;
     }
 }
-
-async fn on_stream_online(
-    graphql_client: &TwitchGqlClient,
-    db: &DatabaseClient,
-    payload: &StreamOnlineV1Payload,
-    state: &mut State,
-) -> Result<()> {
-    let Some(broadcaster) = state
-        .broadcasters
-        .get_mut(&payload.broadcaster_user_id.as_str().parse()?)
-    else {
-        tracing::warn!(
-            "Somehow managed to recv a StreamOnline for a broadcaster who wasn't tracked ({})",
-            payload.broadcaster_user_login
-        );
-        return Ok(());
-    };
-
-    let Some(stream) = graphql_client
-        .get_stream_by_user(payload.broadcaster_user_login.as_str())
-        .await?
-        .stream
-    else {
-        bail!("Failed to find broadcast after StreamOnline for {broadcaster:?}")
-    };
-
-    db.start_broadcast(
-        stream.archive_video.id.parse()?,
-        broadcaster.broadcaster.id,
-        stream.archive_video.title.clone(),
-        timestamp_to_time(&payload.started_at)?,
-    )
-    .await?;
-
-    broadcaster.current_broadcast = Some(stream);
-
-    Ok(())
-}
-
-async fn on_stream_offline(
-    db: &DatabaseClient,
-    timestamp: &Timestamp,
-    payload: &StreamOfflineV1Payload,
-    state: &mut State,
-) -> Result<()> {
-    let Some(broadcaster) = state
-        .broadcasters
-        .get_mut(&payload.broadcaster_user_id.as_str().parse()?)
-    else {
-        tracing::warn!(
-            "Failed to find broadcaster after StreamOffline ({})",
-            payload.broadcaster_user_login
-        );
-        return Ok(());
-    };
-
-    db.end_broadcast(
-        broadcaster.broadcaster.id,
-        timestamp_to_time(timestamp)?,
-        broadcaster
-            .current_broadcast
-            .as_ref()
-            .map(|b| b.archive_video.id.parse().unwrap()),
-    )
-    .await?;
-
-    broadcaster.current_broadcast = None;
-
-    Ok(())
-}
-
-async fn on_chat_message(
-    db: &DatabaseClient,
-    payload: &ChannelChatMessageV1Payload,
-    sent_at: twitch_api::types::Timestamp,
-    state: &State,
-) -> Result<()> {
-    let Some(broadcaster) = state
-        .broadcasters
-        .get(&payload.broadcaster_user_id.as_str().parse()?)
-    else {
-        tracing::warn!(
-            "Somehow managed to recv a message for an untracked broadcaster ({})",
-            payload.broadcaster_user_login
-        );
-        return Ok(());
-    };
-
-    // Messages can be sent while a broadcaster isn't live, and we should skip
-    // these.
-    let Some(broadcast) = broadcaster.current_broadcast.as_ref() else {
-        return Ok(());
-    };
-
-    let message_kind = match &payload.message.text {
-        t if t.starts_with("+2") || t.ends_with("+2") => MessageKind::PlusTwo,
-        t if t.starts_with("-2") || t.ends_with("-2") => MessageKind::MinusTwo,
-        _ => return Ok(()),
-    };
-
-    tracing::info!(
-        name: "ChatMessage",
-        broadcaster = payload.broadcaster_user_name.as_str(),
-        chatter = payload.chatter_user_name.as_str(),
-        kind = ?message_kind
-    );
-
-    // Attempt to insert chatter if they don't exist.
-    db.insert_chatter(
-        payload.chatter_user_id.as_str().parse()?,
-        payload.chatter_user_name.to_string(),
-    )
-    .await?;
-
-    db.insert_message(
-        payload.message_id.as_str().parse()?,
-        broadcast.archive_video.id.parse()?,
-        payload.chatter_user_id.as_str().parse()?,
-        timestamp_to_time(&sent_at)?,
-        message_kind,
-    )
-    .await?;
-
-    Ok(())
-}
-
-fn kind_from_message(message: &CommentsByVideoAndCursorMessage) -> Option<MessageKind> {
-    let first_frag = &message.fragments.first()?.text;
-    let last_frag = &message.fragments.last()?.text;
-
-    Some(
-        if first_frag.starts_with("+2") || last_frag.ends_with("+2") {
-            MessageKind::PlusTwo
-        } else if first_frag.starts_with("-2") || last_frag.ends_with("-2") {
-            MessageKind::MinusTwo
-        } else {
-            return None;
-        },
-    )
-}
-
-fn timestamp_to_time(ts: &Timestamp) -> Result<DateTime> {
-    DateTime::parse_from_str(ts.as_str(), "%Y-%m-%dT%H:%M:%S%.f%Z")
-        .wrap_err("Failed to transform timestamp to datetime")
-}
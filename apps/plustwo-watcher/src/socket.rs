@@ -31,7 +31,7 @@ impl EventSubSocket {
         while let Some(msg) = self.socket.next().await {
             match msg {
                 Ok(tungstenite::Message::Text(msg)) => return Ok(msg),
-                Ok(_) => continue,
+                Ok(_) => {}
                 Err(tungstenite::Error::Protocol(
                     tungstenite::error::ProtocolError::ResetWithoutClosingHandshake,
                 )) => {
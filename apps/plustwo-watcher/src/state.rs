@@ -3,35 +3,55 @@ use std::{
     time::{Duration, Instant},
 };
 
+use chrono::Utc;
 use eyre::Result;
-use plustwo_database::DatabaseClient;
-use plustwo_twitch_gql::TwitchGqlClient;
+use plustwo_classifier::{Cooldowns, Exclusions, OptOuts, Vocabulary};
+use plustwo_database::{Storage, Transactional, entities};
+use plustwo_twitch_gql::{TwitchGqlClient, UserAndStreamByLogin};
 
 use crate::{broadcaster::WatchedBroadcaster, twitch::TwitchClient};
 
+// `Duration::from_mins` isn't available on the toolchain used by the Dockerfile.
+#[allow(clippy::duration_suboptimal_units)]
 const BROADCASTER_REFRESH_RATE: Duration = Duration::from_secs(30 * 60);
+const VOTE_SETTINGS_REFRESH_RATE: Duration = Duration::from_secs(30);
 
 pub struct State {
     pub broadcasters: HashMap<i64, WatchedBroadcaster>,
     pub last_broadcaster_check: Instant,
+    pub vocabulary: Vocabulary,
+    pub vote_patterns_version: i64,
+    pub exclusions: Exclusions,
+    pub exclusions_version: i64,
+    pub opt_outs: OptOuts,
+    pub opt_outs_version: i64,
+    pub last_vote_settings_check: Instant,
     pub session_id: String,
     pub watcher_id: String,
 }
 impl State {
-    pub async fn new(gql: &TwitchGqlClient, watcher: &str) -> Result<Self> {
+    pub async fn new(db: &impl Storage, gql: &TwitchGqlClient, watcher: &str) -> Result<Self> {
         Ok(Self {
             broadcasters: HashMap::new(),
             last_broadcaster_check: Instant::now(),
+            vote_patterns_version: db.get_table_version("vote_patterns").await?,
+            vocabulary: Vocabulary::from_patterns(&db.select_vote_patterns().await?)?,
+            exclusions_version: db.get_table_version("excluded_chatters").await?,
+            exclusions: Exclusions::from_models(&db.select_excluded_chatters().await?),
+            opt_outs_version: db.get_table_version("opted_out_chatters").await?,
+            opt_outs: OptOuts::from_models(&db.select_opted_out_chatters().await?),
+            last_vote_settings_check: Instant::now(),
             session_id: String::new(),
             watcher_id: gql.get_stream_by_user(watcher).await?.id,
         })
     }
+    #[must_use]
     pub fn should_update_broadcasters(&self) -> bool {
         self.last_broadcaster_check.elapsed() > BROADCASTER_REFRESH_RATE
     }
     pub async fn update_broadcasters(
         &mut self,
-        db: &DatabaseClient,
+        db: &impl Transactional,
         gql: &TwitchGqlClient,
         api: &TwitchClient,
     ) -> Result<()> {
@@ -39,15 +59,34 @@ impl State {
         self.last_broadcaster_check = Instant::now();
 
         for broadcaster in new_broadcasters {
-            if self.broadcasters.contains_key(&broadcaster.id) {
+            // Logins and display names can change, so broadcasters are only looked up by ID.
+            let user = gql.get_stream_by_id(&broadcaster.id.to_string()).await?;
+            let broadcaster = if let Some(user) = &user {
+                refresh_profile(db, broadcaster, user).await?
+            } else {
+                tracing::warn!(
+                    name = "MissingBroadcaster",
+                    broadcaster = broadcaster.display_name,
+                    id = broadcaster.id
+                );
+                broadcaster
+            };
+
+            // Broadcasters that are already being watched only need their settings refreshed.
+            if let Some(watched) = self.broadcasters.get_mut(&broadcaster.id) {
+                watched
+                    .cooldowns
+                    .set_window(broadcaster.vote_cooldown_seconds);
+                watched.broadcaster = broadcaster;
                 continue;
             }
+            let Some(user) = user else {
+                continue;
+            };
 
             let mut broadcaster = WatchedBroadcaster {
-                current_broadcast: gql
-                    .get_stream_by_user(&broadcaster.display_name)
-                    .await?
-                    .stream,
+                current_broadcast: user.stream,
+                cooldowns: Cooldowns::new(broadcaster.vote_cooldown_seconds),
                 broadcaster,
                 is_watching: false,
             };
@@ -55,12 +94,117 @@ impl State {
             broadcaster
                 .watch(api, &self.session_id, &self.watcher_id)
                 .await?;
-            broadcaster.catchup(db, gql).await?;
+            broadcaster
+                .catchup(db, gql, &self.vocabulary, &self.exclusions, &self.opt_outs)
+                .await?;
 
             self.broadcasters
                 .insert(broadcaster.broadcaster.id, broadcaster);
         }
 
+        let pruned = db.prune_message_contents(Utc::now().naive_utc()).await?;
+        if pruned > 0 {
+            tracing::info!(name = "PrunedMessageContents", count = pruned);
+        }
+
         Ok(())
     }
+
+    #[must_use]
+    pub fn should_update_vote_settings(&self) -> bool {
+        self.last_vote_settings_check.elapsed() > VOTE_SETTINGS_REFRESH_RATE
+    }
+    /// Reloads the vote patterns, exclusions and opt-outs if they've changed since they were last
+    /// loaded.
+    pub async fn update_vote_settings(&mut self, db: &impl Storage) -> Result<()> {
+        self.last_vote_settings_check = Instant::now();
+
+        self.update_vote_patterns(db).await?;
+        self.update_exclusions(db).await?;
+        self.update_opt_outs(db).await
+    }
+    async fn update_vote_patterns(&mut self, db: &impl Storage) -> Result<()> {
+        let version = db.get_table_version("vote_patterns").await?;
+        if version == self.vote_patterns_version {
+            return Ok(());
+        }
+        self.vote_patterns_version = version;
+
+        // An invalid pattern shouldn't take down the watcher, so keep using the previous
+        // patterns until it's fixed.
+        match Vocabulary::from_patterns(&db.select_vote_patterns().await?) {
+            Ok(vocabulary) => {
+                tracing::info!(name = "ReloadedVotePatterns", version);
+                self.vocabulary = vocabulary;
+            }
+            Err(e) => tracing::warn!(name = "InvalidVotePatterns", version, error = %e),
+        }
+
+        Ok(())
+    }
+    async fn update_exclusions(&mut self, db: &impl Storage) -> Result<()> {
+        let version = db.get_table_version("excluded_chatters").await?;
+        if version == self.exclusions_version {
+            return Ok(());
+        }
+        self.exclusions_version = version;
+
+        tracing::info!(name = "ReloadedExclusions", version);
+        self.exclusions = Exclusions::from_models(&db.select_excluded_chatters().await?);
+
+        Ok(())
+    }
+    async fn update_opt_outs(&mut self, db: &impl Storage) -> Result<()> {
+        let version = db.get_table_version("opted_out_chatters").await?;
+        if version == self.opt_outs_version {
+            return Ok(());
+        }
+        self.opt_outs_version = version;
+
+        tracing::info!(name = "ReloadedOptOuts", version);
+        self.opt_outs = OptOuts::from_models(&db.select_opted_out_chatters().await?);
+
+        Ok(())
+    }
+}
+
+/// Stores the broadcaster's current login, display name and profile image if any have changed.
+async fn refresh_profile(
+    db: &impl Storage,
+    mut broadcaster: entities::broadcasters::Model,
+    user: &UserAndStreamByLogin,
+) -> Result<entities::broadcasters::Model> {
+    let profile = (
+        Some(&user.login),
+        &user.display_name,
+        Some(&user.profile_image_url),
+    );
+    if profile
+        == (
+            broadcaster.login.as_ref(),
+            &broadcaster.display_name,
+            broadcaster.profile_image_url.as_ref(),
+        )
+    {
+        return Ok(broadcaster);
+    }
+
+    db.insert_broadcaster(
+        broadcaster.id,
+        &user.login,
+        &user.display_name,
+        &user.profile_image_url,
+    )
+    .await?;
+    tracing::info!(
+        name = "RefreshedBroadcaster",
+        broadcaster = user.display_name,
+        login = user.login
+    );
+
+    broadcaster.login = Some(user.login.clone());
+    broadcaster.display_name.clone_from(&user.display_name);
+    broadcaster.profile_image_url = Some(user.profile_image_url.clone());
+
+    Ok(broadcaster)
 }
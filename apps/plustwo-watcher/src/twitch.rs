@@ -34,7 +34,7 @@ impl TwitchClient {
         Ok(())
     }
 
-    /// Subscribes to an EventSub event.
+    /// Subscribes to an `EventSub` event.
     pub async fn subscribe<S: twitch_api::eventsub::EventSubscription + Send>(
         &self,
         transport: twitch_api::eventsub::Transport,
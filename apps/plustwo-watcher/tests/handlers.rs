@@ -0,0 +1,343 @@
+//! Checks how the watcher records chat against in-memory storage.
+
+use std::{collections::HashMap, time::Instant};
+
+use chrono::{NaiveDate, TimeZone as _, Utc};
+use plustwo_classifier::{Cooldowns, Exclusions, OptOuts, Vocabulary};
+use plustwo_database::{
+    DateTime, MemoryStorage, Storage, Uuid,
+    entities::{broadcasters, excluded_chatters, opted_out_chatters},
+};
+use plustwo_twitch_gql::{
+    CommentsByVideoAndCursorComment, UserAndStreamByLoginStream, shared::video::TwitchVideo,
+};
+use plustwo_watcher::{
+    broadcaster::WatchedBroadcaster,
+    handlers::{on_chat_message, on_stream_offline},
+    state::State,
+};
+use serde_json::{Value, json};
+use twitch_api::{
+    eventsub::{channel::ChannelChatMessageV1Payload, stream::StreamOfflineV1Payload},
+    types::Timestamp,
+};
+
+const BROADCASTER: i64 = 1;
+const BROADCAST: i64 = 101;
+const SENDER: i64 = 201;
+const TARGET: i64 = 202;
+
+fn at(second: u32) -> DateTime {
+    NaiveDate::from_ymd_opt(2025, 4, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, second))
+        .expect("fixture time should be valid")
+}
+
+fn timestamp(second: u32) -> Timestamp {
+    serde_json::from_value(json!(format!("2025-04-01T00:00:{second:02}Z")))
+        .expect("timestamp should parse")
+}
+
+fn broadcaster(store_message_text: bool) -> WatchedBroadcaster {
+    WatchedBroadcaster {
+        broadcaster: broadcasters::Model {
+            id: BROADCASTER,
+            display_name: "Watched".into(),
+            profile_image_url: None,
+            store_message_text,
+            message_text_retention_days: None,
+            vote_cooldown_seconds: Some(60),
+            login: Some("watched".into()),
+        },
+        current_broadcast: Some(UserAndStreamByLoginStream {
+            id: "1".into(),
+            archive_video: TwitchVideo {
+                created_at: Utc.from_utc_datetime(&at(0)),
+                id: BROADCAST.to_string(),
+                title: "live".into(),
+                length_seconds: 0,
+            },
+        }),
+        is_watching: true,
+        cooldowns: Cooldowns::new(Some(60)),
+    }
+}
+
+/// A watcher following one broadcaster who's live, without connecting to Twitch.
+fn state(broadcaster: WatchedBroadcaster) -> State {
+    State {
+        broadcasters: HashMap::from([(BROADCASTER, broadcaster)]),
+        last_broadcaster_check: Instant::now(),
+        vocabulary: Vocabulary::default(),
+        vote_patterns_version: 0,
+        exclusions: Exclusions::default(),
+        exclusions_version: 0,
+        opt_outs: OptOuts::default(),
+        opt_outs_version: 0,
+        last_vote_settings_check: Instant::now(),
+        session_id: String::new(),
+        watcher_id: String::new(),
+    }
+}
+
+/// Storage with the live broadcast already started.
+async fn storage() -> MemoryStorage {
+    let db = MemoryStorage::new();
+    db.insert_broadcaster(BROADCASTER, "watched", "Watched", "")
+        .await
+        .unwrap();
+    db.start_broadcast(BROADCAST, BROADCASTER, "live".into(), at(0))
+        .await
+        .unwrap();
+
+    db
+}
+
+fn text(text: &str) -> Value {
+    json!({ "type": "text", "text": text, "cheermote": null, "emote": null, "mention": null })
+}
+
+fn mention() -> Value {
+    json!({
+        "type": "mention",
+        "text": "@Bob",
+        "cheermote": null,
+        "emote": null,
+        "mention": { "user_id": TARGET.to_string(), "user_login": "bob", "user_name": "Bob" },
+    })
+}
+
+fn chat_message(id: u128, fragments: &[Value]) -> ChannelChatMessageV1Payload {
+    let text: String = fragments
+        .iter()
+        .filter_map(|f| f["text"].as_str())
+        .collect();
+
+    serde_json::from_value(json!({
+        "broadcaster_user_id": BROADCASTER.to_string(),
+        "broadcaster_user_login": "watched",
+        "broadcaster_user_name": "Watched",
+        "chatter_user_id": SENDER.to_string(),
+        "chatter_user_login": "sender",
+        "chatter_user_name": "Sender",
+        "message_id": Uuid::from_u128(id).to_string(),
+        "message": { "text": text, "fragments": fragments },
+        "color": "",
+        "badges": [],
+        "message_type": "text",
+        "cheer": null,
+        "reply": null,
+        "channel_points_custom_reward_id": null,
+        "source_broadcaster_user_id": null,
+        "source_broadcaster_user_login": null,
+        "source_broadcaster_user_name": null,
+        "source_message_id": null,
+        "source_badges": null,
+    }))
+    .expect("chat message should parse")
+}
+
+#[tokio::test]
+async fn opted_out_and_excluded_voters_are_ignored() {
+    let db = storage().await;
+    let mut state = state(broadcaster(false));
+    state.opt_outs = OptOuts::from_models(&[opted_out_chatters::Model {
+        chatter_id: SENDER,
+        reason: None,
+        opted_out_at: at(0),
+    }]);
+
+    on_chat_message(
+        &db,
+        &chat_message(1, &[text("+2")]),
+        timestamp(1),
+        &mut state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(db.get_chatter(SENDER).await.unwrap(), None);
+
+    state.opt_outs = OptOuts::default();
+    state.exclusions = Exclusions::from_models(&[excluded_chatters::Model {
+        id: 1,
+        broadcaster_id: None,
+        chatter_id: SENDER,
+        reason: Some("bot".into()),
+    }]);
+    on_chat_message(
+        &db,
+        &chat_message(2, &[text("+2")]),
+        timestamp(2),
+        &mut state,
+    )
+    .await
+    .unwrap();
+    assert!(db.select_messages(BROADCAST).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn votes_within_the_cooldown_are_suppressed() {
+    let db = storage().await;
+    let mut state = state(broadcaster(false));
+
+    for (id, second) in [(1, 1), (2, 30)] {
+        on_chat_message(
+            &db,
+            &chat_message(id, &[text("+2")]),
+            timestamp(second),
+            &mut state,
+        )
+        .await
+        .unwrap();
+    }
+
+    let mut votes = db.select_messages(BROADCAST).await.unwrap();
+    votes.sort_by_key(|vote| vote.sent_at);
+    assert_eq!(
+        votes.iter().map(|vote| vote.suppressed).collect::<Vec<_>>(),
+        [false, true]
+    );
+    assert_eq!(
+        db.select_minute_stats(BROADCAST).await.unwrap()[0].plus_count,
+        1
+    );
+}
+
+#[tokio::test]
+async fn targeted_votes_record_their_target() {
+    let db = storage().await;
+    let mut state = state(broadcaster(true));
+
+    on_chat_message(
+        &db,
+        &chat_message(1, &[text("+2 "), mention()]),
+        timestamp(1),
+        &mut state,
+    )
+    .await
+    .unwrap();
+
+    let votes = db.select_messages(BROADCAST).await.unwrap();
+    assert_eq!(votes[0].target_chatter_id, Some(TARGET));
+    assert_eq!(
+        db.get_chatter(TARGET).await.unwrap().unwrap().display_name,
+        "Bob"
+    );
+    assert_eq!(
+        db.select_message_contents(BROADCAST, None, None)
+            .await
+            .unwrap()[0]
+            .text,
+        "+2 @Bob"
+    );
+}
+
+#[tokio::test]
+async fn unknown_broadcasts_are_left_alone_when_going_offline() {
+    // The broadcast started before the watcher did, so it was never recorded.
+    let db = MemoryStorage::new();
+    let mut state = state(broadcaster(false));
+    let payload: StreamOfflineV1Payload = serde_json::from_value(json!({
+        "broadcaster_user_id": BROADCASTER.to_string(),
+        "broadcaster_user_login": "watched",
+        "broadcaster_user_name": "Watched",
+    }))
+    .expect("stream offline should parse");
+
+    on_stream_offline(&db, &timestamp(59), &payload, &mut state)
+        .await
+        .unwrap();
+    assert!(state.broadcasters[&BROADCASTER].current_broadcast.is_none());
+    assert!(
+        db.select_broadcasts(None, None, None)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+fn comment(
+    id: u128,
+    second: u32,
+    commenter: i64,
+    fragments: &[Value],
+) -> CommentsByVideoAndCursorComment {
+    serde_json::from_value(json!({
+        "commenter": { "id": commenter.to_string(), "displayName": format!("chatter{commenter}") },
+        "createdAt": format!("2025-04-01T00:00:{second:02}Z"),
+        "id": Uuid::from_u128(id),
+        "message": { "fragments": fragments },
+    }))
+    .expect("comment should parse")
+}
+
+#[tokio::test]
+async fn catchup_imports_comments_together() {
+    let db = MemoryStorage::new();
+    let mut broadcaster = broadcaster(true);
+    let opted_out = SENDER + 10;
+    let comments = vec![
+        comment(
+            1,
+            1,
+            SENDER,
+            &[
+                json!({ "text": "+2 " }),
+                json!({
+                    "text": "@Bob",
+                    "mention": { "id": TARGET.to_string(), "displayName": "Bob" },
+                }),
+            ],
+        ),
+        comment(2, 2, SENDER, &[json!({ "text": "+2" })]),
+        comment(3, 3, TARGET, &[json!({ "text": "hello" })]),
+        comment(4, 4, opted_out, &[json!({ "text": "-2" })]),
+    ];
+    let opt_outs = OptOuts::from_models(&[opted_out_chatters::Model {
+        chatter_id: opted_out,
+        reason: None,
+        opted_out_at: at(0),
+    }]);
+
+    broadcaster
+        .import(
+            &db,
+            comments,
+            &Vocabulary::default(),
+            &Exclusions::default(),
+            &opt_outs,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        db.get_broadcast(BROADCAST).await.unwrap().unwrap().title,
+        "live"
+    );
+    let mut votes = db.select_messages(BROADCAST).await.unwrap();
+    votes.sort_by_key(|vote| vote.sent_at);
+    assert_eq!(
+        votes
+            .iter()
+            .map(|vote| (vote.target_chatter_id, vote.suppressed))
+            .collect::<Vec<_>>(),
+        [(Some(TARGET), false), (None, true)]
+    );
+    // Only votes have their text stored.
+    assert_eq!(
+        db.select_message_contents(BROADCAST, None, None)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        db.select_minute_stats(BROADCAST).await.unwrap()[0].plus_count,
+        1
+    );
+    assert!(db.get_chatter(TARGET).await.unwrap().is_some());
+    assert_eq!(db.get_chatter(opted_out).await.unwrap(), None);
+
+    // Live votes share the cooldowns used during catchup.
+    assert!(broadcaster.cooldowns.suppress(SENDER, at(30)));
+}
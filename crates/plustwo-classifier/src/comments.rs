@@ -0,0 +1,143 @@
+use std::{fmt, num::ParseIntError};
+
+use plustwo_database::{
+    DateTime,
+    entities::{broadcasters, chatters, message_contents, message_roles, messages},
+};
+use plustwo_twitch_gql::CommentsByVideoAndCursorComment;
+
+use crate::{Cooldowns, Exclusions, NormalizedMessage, OptOuts, Vocabulary};
+
+/// The rows to insert for a broadcast's VOD comments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommentRows {
+    /// Every chatter seen, along with when they were seen with that name.
+    pub chatters: Vec<(chatters::Model, DateTime)>,
+    pub messages: Vec<messages::Model>,
+    pub contents: Vec<message_contents::Model>,
+    pub roles: Vec<message_roles::Model>,
+}
+impl CommentRows {
+    /// Classifies a broadcast's comments, in the order they were sent. Opted out chatters are
+    /// skipped entirely, and votes are suppressed as they're fed through `cooldowns`.
+    pub fn from_comments(
+        comments: Vec<CommentsByVideoAndCursorComment>,
+        broadcaster: &broadcasters::Model,
+        broadcast_id: i64,
+        vocabulary: &Vocabulary,
+        exclusions: &Exclusions,
+        opt_outs: &OptOuts,
+        cooldowns: &mut Cooldowns,
+    ) -> Result<Self, InvalidComment> {
+        let mut rows = Self::default();
+
+        for comment in comments {
+            // Some users don't show up. Maybe they've deleted their account or been banned?
+            let Some(user) = comment.commenter else {
+                continue;
+            };
+            let chatter = chatters::Model {
+                id: user.id.parse()?,
+                display_name: user.display_name,
+            };
+            // Chatters who've opted out are never recorded.
+            if opt_outs.is_opted_out(chatter.id) {
+                continue;
+            }
+
+            // Only votes are recorded, and votes from excluded chatters are ignored.
+            let message = NormalizedMessage::from_comment(&user.id, &comment.message);
+            let Some(classification) = crate::classify_with(vocabulary, broadcaster.id, &message)
+                .filter(|_| !exclusions.is_excluded(broadcaster.id, chatter.id))
+            else {
+                continue;
+            };
+
+            let sent_at = comment.created_at.naive_utc();
+            rows.chatters.push((chatter.clone(), sent_at));
+
+            let roles = comment.message.roles();
+            rows.roles.push(message_roles::Model {
+                id: comment.id,
+                is_subscriber: roles.subscriber,
+                is_moderator: roles.moderator,
+                is_vip: roles.vip,
+                is_founder: roles.founder,
+                subscriber_months: roles.subscriber_months,
+            });
+
+            // Votes for chatters who've opted out still count, without recording who they were
+            // for.
+            let target = classification
+                .target
+                .map(|t| chatters::Model::try_from(t.chatter))
+                .transpose()?
+                .filter(|t| !opt_outs.is_opted_out(t.id));
+
+            rows.messages.push(messages::Model {
+                id: comment.id,
+                broadcast_id,
+                chatter_id: chatter.id,
+                sent_at,
+                message_kind: classification.kind,
+                target_chatter_id: target.as_ref().map(|t| t.id),
+                value: classification.value,
+                suppressed: cooldowns.suppress(chatter.id, sent_at),
+            });
+
+            if let Some(target) = target {
+                rows.chatters.push((target, sent_at));
+            }
+            if broadcaster.store_message_text {
+                rows.contents.push(message_contents::Model {
+                    id: comment.id,
+                    broadcast_id,
+                    chatter_id: chatter.id,
+                    sent_at,
+                    text: message.text,
+                    fragments: serde_json::to_value(&message.fragments)?,
+                    // VOD comments don't say what they're replying to.
+                    reply_to_chatter_id: None,
+                    reply_to_display_name: None,
+                });
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+/// A comment which couldn't be converted into rows.
+#[derive(Debug)]
+pub enum InvalidComment {
+    /// A chatter's ID wasn't a number.
+    ChatterId(ParseIntError),
+    /// The message's fragments couldn't be serialized for storage.
+    Fragments(serde_json::Error),
+}
+impl From<ParseIntError> for InvalidComment {
+    fn from(error: ParseIntError) -> Self {
+        Self::ChatterId(error)
+    }
+}
+impl From<serde_json::Error> for InvalidComment {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Fragments(error)
+    }
+}
+impl fmt::Display for InvalidComment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChatterId(error) => write!(f, "comment has an invalid chatter ID: {error}"),
+            Self::Fragments(error) => write!(f, "comment fragments couldn't be stored: {error}"),
+        }
+    }
+}
+impl std::error::Error for InvalidComment {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ChatterId(error) => Some(error),
+            Self::Fragments(error) => Some(error),
+        }
+    }
+}
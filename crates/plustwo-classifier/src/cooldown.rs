@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use chrono::TimeDelta;
+use plustwo_database::DateTime;
+
+/// Tracks each chatter's most recent counted vote, so that votes sent too soon afterwards can
+/// be suppressed. Votes must be fed in the order they were sent.
+#[derive(Debug, Clone, Default)]
+pub struct Cooldowns {
+    window: Option<TimeDelta>,
+    last_counted: HashMap<i64, DateTime>,
+}
+impl Cooldowns {
+    /// Constructs a tracker for a broadcaster's cooldown. No window, or a window of zero or
+    /// less, means votes are never suppressed.
+    #[must_use]
+    pub fn new(window_seconds: Option<i32>) -> Self {
+        let mut cooldowns = Self::default();
+        cooldowns.set_window(window_seconds);
+
+        cooldowns
+    }
+
+    /// Changes the cooldown window, keeping track of the votes which have already counted.
+    pub fn set_window(&mut self, window_seconds: Option<i32>) {
+        self.window = window_seconds
+            .filter(|s| *s > 0)
+            .map(|s| TimeDelta::seconds(s.into()));
+    }
+
+    /// Records a vote, returning whether it falls within the window started by the chatter's
+    /// last counted vote. Suppressed votes don't extend the window.
+    pub fn suppress(&mut self, chatter_id: i64, sent_at: DateTime) -> bool {
+        let Some(window) = self.window else {
+            return false;
+        };
+
+        if self
+            .last_counted
+            .get(&chatter_id)
+            .is_some_and(|last| sent_at < *last + window)
+        {
+            return true;
+        }
+
+        self.last_counted.insert(chatter_id, sent_at);
+        false
+    }
+
+    /// Forgets every counted vote, such as when a new broadcast starts.
+    pub fn clear(&mut self) {
+        self.last_counted.clear();
+    }
+}
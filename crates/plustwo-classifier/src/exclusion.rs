@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use plustwo_database::entities::{excluded_chatters, opted_out_chatters};
+
+/// Chatters whose votes are ignored, such as chat bots.
+#[derive(Debug, Clone, Default)]
+pub struct Exclusions {
+    /// Pairs of the broadcaster the exclusion applies to (`None` for every broadcaster) and the
+    /// excluded chatter.
+    excluded: HashSet<(Option<i64>, i64)>,
+}
+impl Exclusions {
+    #[must_use]
+    pub fn from_models(models: &[excluded_chatters::Model]) -> Self {
+        Self {
+            excluded: models
+                .iter()
+                .map(|m| (m.broadcaster_id, m.chatter_id))
+                .collect(),
+        }
+    }
+
+    /// Whether votes from the chatter should be ignored in the broadcaster's chat. Broadcasters
+    /// are always excluded from their own chat.
+    #[must_use]
+    pub fn is_excluded(&self, broadcaster_id: i64, chatter_id: i64) -> bool {
+        chatter_id == broadcaster_id
+            || self.excluded.contains(&(None, chatter_id))
+            || self.excluded.contains(&(Some(broadcaster_id), chatter_id))
+    }
+}
+
+/// Chatters who have asked never to be recorded, whether they send a message or are targeted by
+/// one.
+#[derive(Debug, Clone, Default)]
+pub struct OptOuts {
+    opted_out: HashSet<i64>,
+}
+impl OptOuts {
+    #[must_use]
+    pub fn from_models(models: &[opted_out_chatters::Model]) -> Self {
+        Self {
+            opted_out: models.iter().map(|m| m.chatter_id).collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_opted_out(&self, chatter_id: i64) -> bool {
+        self.opted_out.contains(&chatter_id)
+    }
+}
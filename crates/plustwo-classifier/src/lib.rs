@@ -0,0 +1,149 @@
+use std::fmt;
+
+use plustwo_database::entities::sea_orm_active_enums::MessageKind;
+use serde::{Deserialize, Serialize};
+
+pub mod comments;
+pub mod cooldown;
+pub mod exclusion;
+pub mod message;
+pub mod tokenizer;
+pub mod vocabulary;
+
+pub use comments::{CommentRows, InvalidComment};
+pub use cooldown::Cooldowns;
+pub use exclusion::{Exclusions, OptOuts};
+pub use message::{Chatter, Fragment, NormalizedMessage};
+pub use vocabulary::{InvalidPattern, Vocabulary};
+
+/// The result of classifying a message as a vote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Classification {
+    pub kind: MessageKind,
+    /// The signed magnitude of the vote, such as 5 for "+5".
+    pub value: i32,
+    /// The rule which caused the message to be classified as `kind`.
+    pub rule: Rule,
+    /// The chatter the vote was aimed at, if any.
+    pub target: Option<Target>,
+}
+impl Classification {
+    /// Describes why the message was classified the way it was.
+    #[must_use]
+    pub fn explain(&self) -> String {
+        let mut explanation = format!("{:?}: {}", self.kind, self.rule);
+        if let Some(target) = &self.target {
+            explanation = format!("{explanation}, {}", target.rule);
+        }
+
+        explanation
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// The message started with the literal.
+    Prefix(String),
+    /// The message ended with the literal.
+    Suffix(String),
+    /// The message contained the vote somewhere in the middle.
+    Token(String),
+    /// The message matched the expression.
+    Regex(String),
+    /// The message contained the emote.
+    Emote(String),
+}
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Prefix(token) => write!(f, "message starts with \"{token}\""),
+            Self::Suffix(token) => write!(f, "message ends with \"{token}\""),
+            Self::Token(token) => write!(f, "message contains \"{token}\""),
+            Self::Regex(regex) => write!(f, "message matches /{regex}/"),
+            Self::Emote(id) => write!(f, "message contains emote {id}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    pub chatter: Chatter,
+    pub rule: TargetRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetRule {
+    /// The message was a reply to the target's message.
+    Reply,
+    /// The target was the first chatter mentioned in the message.
+    Mention,
+}
+impl fmt::Display for TargetRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reply => write!(f, "aimed at the chatter being replied to"),
+            Self::Mention => write!(f, "aimed at the first mentioned chatter"),
+        }
+    }
+}
+
+/// Classifies a message as a vote using the default (empty) vocabulary, returning `None` if it
+/// isn't one.
+#[must_use]
+pub fn classify(message: &NormalizedMessage) -> Option<Classification> {
+    classify_with(&Vocabulary::default(), 0, message)
+}
+
+/// Classifies a message sent to a broadcaster as a vote, returning `None` if it isn't one.
+///
+/// Numeric votes are always recognised and take priority over the vocabulary. The message is
+/// NFKC normalized first, so patterns are matched against the normalized text.
+#[must_use]
+pub fn classify_with(
+    vocabulary: &Vocabulary,
+    broadcaster_id: i64,
+    message: &NormalizedMessage,
+) -> Option<Classification> {
+    let text = tokenizer::normalize(&message.vote_text());
+    let text = text.trim_matches(|c: char| c.is_whitespace() || c == message::EMOTE_PLACEHOLDER);
+
+    let (kind, value, rule) = tokenizer::find(text).or_else(|| {
+        vocabulary
+            .find(broadcaster_id, text, message)
+            .map(|(kind, rule)| (kind.clone(), tokenizer::default_value(&kind), rule))
+    })?;
+
+    Some(Classification {
+        kind,
+        value,
+        rule,
+        target: target(message),
+    })
+}
+
+/// Finds the chatter a message is aimed at. Replies are always aimed at the parent message's
+/// chatter, otherwise whoever was mentioned first. Chatters aiming a vote at themselves are
+/// treated as untargeted.
+fn target(message: &NormalizedMessage) -> Option<Target> {
+    message
+        .reply_to
+        .clone()
+        .map(|chatter| Target {
+            chatter,
+            rule: TargetRule::Reply,
+        })
+        .or_else(|| {
+            message
+                .fragments
+                .iter()
+                .find_map(|f| match f {
+                    Fragment::Mention { chatter, .. } => Some(chatter.clone()),
+                    _ => None,
+                })
+                .map(|chatter| Target {
+                    chatter,
+                    rule: TargetRule::Mention,
+                })
+        })
+        .filter(|t| t.chatter.id != message.chatter_id)
+}
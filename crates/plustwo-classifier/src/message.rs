@@ -0,0 +1,208 @@
+use std::num::ParseIntError;
+
+use plustwo_database::entities::chatters;
+use plustwo_twitch_gql::{
+    CommentsByVideoAndCursorFragment, CommentsByVideoAndCursorMention,
+    CommentsByVideoAndCursorMessage,
+};
+use serde::{Deserialize, Serialize};
+
+/// Stands in for emotes and cheermotes in [`NormalizedMessage::vote_text`].
+pub const EMOTE_PLACEHOLDER: char = '\u{FFFC}';
+
+/// A chat message from either a live broadcast or a VOD, in a common shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedMessage {
+    /// The ID of the chatter who sent the message.
+    pub chatter_id: String,
+    /// The full text of the message.
+    pub text: String,
+    pub fragments: Vec<Fragment>,
+    /// The chatter who sent the message being replied to, if any.
+    pub reply_to: Option<Chatter>,
+}
+impl NormalizedMessage {
+    /// Constructs a message from its fragments, joining them for the full text.
+    pub fn from_fragments(chatter_id: impl Into<String>, fragments: Vec<Fragment>) -> Self {
+        Self {
+            chatter_id: chatter_id.into(),
+            text: fragments.iter().map(Fragment::as_str).collect(),
+            fragments,
+            reply_to: None,
+        }
+    }
+
+    /// The text which votes are read from. Emotes and cheermotes are replaced with
+    /// [`EMOTE_PLACEHOLDER`], so their names can't be mistaken for votes and they can't join the
+    /// text around them into a single vote.
+    #[must_use]
+    pub fn vote_text(&self) -> String {
+        if self.fragments.is_empty() {
+            return self.text.clone();
+        }
+
+        let mut vote_text = String::new();
+        for fragment in &self.fragments {
+            match fragment {
+                Fragment::Text { text } | Fragment::Mention { text, .. } => {
+                    vote_text.push_str(text);
+                }
+                Fragment::Emote { .. } | Fragment::Cheermote { .. } => {
+                    vote_text.push(EMOTE_PLACEHOLDER);
+                }
+            }
+        }
+
+        vote_text
+    }
+
+    /// Constructs a message from a VOD comment.
+    pub fn from_comment(
+        chatter_id: impl Into<String>,
+        message: &CommentsByVideoAndCursorMessage,
+    ) -> Self {
+        Self::from_fragments(
+            chatter_id,
+            message.fragments.iter().map(Fragment::from).collect(),
+        )
+    }
+}
+
+/// A single piece of a message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredFragment", into = "StoredFragment")]
+pub enum Fragment {
+    Text {
+        text: String,
+    },
+    Emote {
+        text: String,
+        emote_id: String,
+    },
+    Mention {
+        text: String,
+        chatter: Chatter,
+    },
+    /// A cheer, which is only ever seen live. VODs show cheers as plain text.
+    Cheermote {
+        text: String,
+        prefix: String,
+        bits: i32,
+    },
+}
+impl Fragment {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    /// The fragment's text, as it appeared in chat.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Text { text }
+            | Self::Emote { text, .. }
+            | Self::Mention { text, .. }
+            | Self::Cheermote { text, .. } => text,
+        }
+    }
+}
+impl From<&CommentsByVideoAndCursorFragment> for Fragment {
+    fn from(fragment: &CommentsByVideoAndCursorFragment) -> Self {
+        let text = fragment.text.clone();
+
+        if let Some(mention) = &fragment.mention {
+            Self::Mention {
+                text,
+                chatter: Chatter::from(mention),
+            }
+        } else if let Some(emote) = &fragment.emote {
+            Self::Emote {
+                text,
+                emote_id: emote.emote_id.clone(),
+            }
+        } else {
+            Self::Text { text }
+        }
+    }
+}
+
+/// The shape message fragments are stored in. Each kind of fragment is told apart by which
+/// field is present, which keeps fragments stored before they had kinds readable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredFragment {
+    text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mention: Option<Chatter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    emote_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cheermote: Option<StoredCheermote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCheermote {
+    prefix: String,
+    bits: i32,
+}
+
+impl From<StoredFragment> for Fragment {
+    fn from(stored: StoredFragment) -> Self {
+        let text = stored.text;
+
+        match (stored.mention, stored.emote_id, stored.cheermote) {
+            (Some(chatter), _, _) => Self::Mention { text, chatter },
+            (None, Some(emote_id), _) => Self::Emote { text, emote_id },
+            (None, None, Some(StoredCheermote { prefix, bits })) => {
+                Self::Cheermote { text, prefix, bits }
+            }
+            (None, None, None) => Self::Text { text },
+        }
+    }
+}
+impl From<Fragment> for StoredFragment {
+    fn from(fragment: Fragment) -> Self {
+        let mut stored = Self {
+            text: fragment.as_str().to_string(),
+            mention: None,
+            emote_id: None,
+            cheermote: None,
+        };
+
+        match fragment {
+            Fragment::Text { .. } => {}
+            Fragment::Emote { emote_id, .. } => stored.emote_id = Some(emote_id),
+            Fragment::Mention { chatter, .. } => stored.mention = Some(chatter),
+            Fragment::Cheermote { prefix, bits, .. } => {
+                stored.cheermote = Some(StoredCheermote { prefix, bits });
+            }
+        }
+
+        stored
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chatter {
+    pub id: String,
+    pub display_name: String,
+}
+impl From<&CommentsByVideoAndCursorMention> for Chatter {
+    fn from(mention: &CommentsByVideoAndCursorMention) -> Self {
+        Self {
+            id: mention.id.clone(),
+            display_name: mention.display_name.clone(),
+        }
+    }
+}
+impl TryFrom<Chatter> for chatters::Model {
+    type Error = ParseIntError;
+
+    fn try_from(chatter: Chatter) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: chatter.id.parse()?,
+            display_name: chatter.display_name,
+        })
+    }
+}
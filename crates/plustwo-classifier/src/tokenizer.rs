@@ -0,0 +1,170 @@
+use plustwo_database::entities::sea_orm_active_enums::MessageKind;
+use unicode_normalization::UnicodeNormalization as _;
+
+use crate::{Rule, message::EMOTE_PLACEHOLDER};
+
+/// The largest magnitude a vote can have. Bigger numbers, like "-2000 ping", aren't votes.
+pub const MAX_MAGNITUDE: i32 = 1000;
+
+/// A signed number standing on its own in normalized text, like "+2", "+ 2" or "2+".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteToken {
+    pub value: i32,
+    /// The byte range of the token in the normalized text.
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Applies NFKC normalization, so full-width and other compatibility forms like "＋２" read the
+/// same as their ASCII equivalents. The minus sign (U+2212) is also treated as a hyphen.
+#[must_use]
+pub fn normalize(text: &str) -> String {
+    text.nfkc()
+        .map(|c| if c == '\u{2212}' { '-' } else { c })
+        .collect()
+}
+
+/// Finds every vote token in normalized text.
+///
+/// Tokens must sit at word boundaries, so "covid-19", "1-2" and "2025-04-15" aren't votes, and a
+/// sign may be separated from its digits by whitespace ("+ 2"). Signs after a plain number and
+/// whitespace are arithmetic, so "10 - 2" isn't a vote either.
+///
+/// A sign may also follow its digits ("2+"), but only when that's all the message says besides
+/// emotes, since "18+ only" and "top 5+" read as "or more".
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<VoteToken> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let boundary = |i: usize| chars.get(i).is_none_or(|(_, c)| !c.is_alphanumeric());
+    let blank = |chars: &[(usize, char)]| {
+        chars
+            .iter()
+            .all(|(_, c)| c.is_whitespace() || *c == EMOTE_PLACEHOLDER)
+    };
+    let offset = |i: usize| chars.get(i).map_or(text.len(), |(offset, _)| *offset);
+    let digits_from = |i: usize| {
+        i + chars[i..]
+            .iter()
+            .take_while(|(_, c)| c.is_ascii_digit())
+            .count()
+    };
+
+    let mut tokens = Vec::new();
+    // Where the last number without a sign of its own ended.
+    let mut plain_number_end = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let preceded_by_boundary = i == 0 || boundary(i - 1);
+        let arithmetic = plain_number_end
+            .is_some_and(|end| end < i && chars[end..i].iter().all(|(_, c)| c.is_whitespace()));
+
+        if let (Some(negative), true) = (sign(c), preceded_by_boundary && !arithmetic) {
+            // A leading sign, optionally followed by whitespace.
+            let first_digit = i
+                + 1
+                + chars[i + 1..]
+                    .iter()
+                    .take_while(|(_, c)| c.is_whitespace())
+                    .count();
+            let end = digits_from(first_digit);
+            if end > first_digit && boundary(end) {
+                push(
+                    &mut tokens,
+                    negative,
+                    &text[offset(first_digit)..offset(end)],
+                    start,
+                    offset(end),
+                );
+                i = end;
+                continue;
+            }
+        } else if c.is_ascii_digit() && preceded_by_boundary {
+            // Digits, optionally followed by a trailing sign.
+            let end = digits_from(i);
+            let trailing_sign = chars
+                .get(end)
+                .and_then(|(_, c)| sign(*c))
+                .filter(|_| blank(&chars[..i]) && blank(&chars[end + 1..]));
+            if let Some(negative) = trailing_sign {
+                push(
+                    &mut tokens,
+                    negative,
+                    &text[start..offset(end)],
+                    start,
+                    offset(end + 1),
+                );
+            } else {
+                plain_number_end = Some(end);
+            }
+            i = end.max(i + 1);
+            continue;
+        }
+
+        i += 1;
+    }
+
+    tokens
+}
+
+/// Finds the vote in the text. When there are votes of both signs, like "-2 +2", the first
+/// positive vote wins, and neutral votes like "+0" only count when there's no signed vote.
+#[must_use]
+pub fn find(text: &str) -> Option<(MessageKind, i32, Rule)> {
+    let tokens = tokenize(text);
+    let token = tokens
+        .iter()
+        .find(|t| t.value > 0)
+        .or_else(|| tokens.iter().find(|t| t.value < 0))
+        .or_else(|| tokens.first())?;
+
+    let kind = match token.value.signum() {
+        1 => MessageKind::PlusTwo,
+        -1 => MessageKind::MinusTwo,
+        _ => MessageKind::PlusZero,
+    };
+    let canonical = format!("{:+}", token.value);
+    let rule = if token.start == 0 {
+        Rule::Prefix(canonical)
+    } else if token.end == text.len() {
+        Rule::Suffix(canonical)
+    } else {
+        Rule::Token(canonical)
+    };
+
+    Some((kind, token.value, rule))
+}
+
+/// The value of a vote which didn't include its own number.
+#[must_use]
+pub const fn default_value(kind: &MessageKind) -> i32 {
+    match kind {
+        MessageKind::PlusTwo | MessageKind::W => 2,
+        MessageKind::MinusTwo | MessageKind::L => -2,
+        MessageKind::PlusZero => 0,
+    }
+}
+
+const fn sign(c: char) -> Option<bool> {
+    match c {
+        '+' => Some(false),
+        '-' => Some(true),
+        _ => None,
+    }
+}
+
+/// Adds a token, unless it's bigger than [`MAX_MAGNITUDE`].
+fn push(tokens: &mut Vec<VoteToken>, negative: bool, digits: &str, start: usize, end: usize) {
+    let Some(value) = digits
+        .parse::<i32>()
+        .ok()
+        .filter(|value| *value <= MAX_MAGNITUDE)
+    else {
+        return;
+    };
+    tokens.push(VoteToken {
+        value: if negative { -value } else { value },
+        start,
+        end,
+    });
+}
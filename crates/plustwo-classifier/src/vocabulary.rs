@@ -0,0 +1,131 @@
+use std::fmt;
+
+use plustwo_database::entities::{
+    sea_orm_active_enums::{MessageKind, VotePatternKind},
+    vote_patterns,
+};
+use regex::Regex;
+
+use crate::{Fragment, NormalizedMessage, Rule, tokenizer};
+
+/// A set of patterns which turn messages into votes.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    patterns: Vec<VotePattern>,
+}
+impl Vocabulary {
+    /// Compiles the stored vote patterns, failing if any of them are invalid.
+    pub fn from_patterns(patterns: &[vote_patterns::Model]) -> Result<Self, InvalidPattern> {
+        Ok(Self {
+            patterns: patterns
+                .iter()
+                .map(VotePattern::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Finds the first pattern matching the message, where `text` is the message's vote text.
+    /// A broadcaster's own patterns are checked before the global patterns, and otherwise
+    /// patterns are checked in the order they were given.
+    pub(crate) fn find(
+        &self,
+        broadcaster_id: i64,
+        text: &str,
+        message: &NormalizedMessage,
+    ) -> Option<(MessageKind, Rule)> {
+        self.patterns
+            .iter()
+            .filter(|p| p.broadcaster_id == Some(broadcaster_id))
+            .chain(self.patterns.iter().filter(|p| p.broadcaster_id.is_none()))
+            .find_map(|p| p.matcher.find(text, message).map(|r| (p.kind.clone(), r)))
+    }
+}
+#[derive(Debug, Clone)]
+struct VotePattern {
+    /// The broadcaster the pattern applies to, or `None` for every broadcaster.
+    broadcaster_id: Option<i64>,
+    matcher: Matcher,
+    kind: MessageKind,
+}
+impl TryFrom<&vote_patterns::Model> for VotePattern {
+    type Error = InvalidPattern;
+
+    fn try_from(model: &vote_patterns::Model) -> Result<Self, Self::Error> {
+        let matcher = match model.pattern_kind {
+            // Messages are normalized before they're matched, so literals need to be as well.
+            VotePatternKind::Literal => Matcher::Literal(tokenizer::normalize(&model.pattern)),
+            VotePatternKind::Regex => {
+                Matcher::Regex(Regex::new(&model.pattern).map_err(|source| InvalidPattern {
+                    id: model.id,
+                    source,
+                })?)
+            }
+            VotePatternKind::Emote => Matcher::Emote(model.pattern.clone()),
+        };
+
+        Ok(Self {
+            broadcaster_id: model.broadcaster_id,
+            matcher,
+            kind: model.message_kind.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// Matches messages which start or end with the text as a whole word, so "L" doesn't match
+    /// "LUL".
+    Literal(String),
+    /// Matches messages which match the expression anywhere in their text.
+    Regex(Regex),
+    /// Matches messages which contain the emote, by ID.
+    Emote(String),
+}
+impl Matcher {
+    fn find(&self, text: &str, message: &NormalizedMessage) -> Option<Rule> {
+        match self {
+            Self::Literal(literal) => {
+                let boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+                if text
+                    .strip_prefix(literal.as_str())
+                    .is_some_and(|rest| boundary(rest.chars().next()))
+                {
+                    Some(Rule::Prefix(literal.clone()))
+                } else if text
+                    .strip_suffix(literal.as_str())
+                    .is_some_and(|rest| boundary(rest.chars().next_back()))
+                {
+                    Some(Rule::Suffix(literal.clone()))
+                } else {
+                    None
+                }
+            }
+            Self::Regex(regex) => regex
+                .is_match(text)
+                .then(|| Rule::Regex(regex.as_str().to_string())),
+            Self::Emote(id) => message
+                .fragments
+                .iter()
+                .any(|f| matches!(f, Fragment::Emote { emote_id, .. } if emote_id == id))
+                .then(|| Rule::Emote(id.clone())),
+        }
+    }
+}
+
+/// A stored vote pattern which couldn't be compiled.
+#[derive(Debug)]
+pub struct InvalidPattern {
+    /// The ID of the offending pattern.
+    pub id: i32,
+    pub source: regex::Error,
+}
+impl fmt::Display for InvalidPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "vote pattern {} is invalid: {}", self.id, self.source)
+    }
+}
+impl std::error::Error for InvalidPattern {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
@@ -0,0 +1,117 @@
+use plustwo_classifier::{CommentRows, Cooldowns, Exclusions, OptOuts, Vocabulary};
+use plustwo_database::{
+    Uuid,
+    entities::{broadcasters, excluded_chatters, opted_out_chatters},
+};
+use plustwo_twitch_gql::CommentsByVideoAndCursorComment;
+use serde_json::{Value, json};
+
+const BROADCASTER: i64 = 10;
+const BROADCAST: i64 = 100;
+const VOTER: i64 = 20;
+const TARGET: i64 = 30;
+const BOT: i64 = 40;
+
+fn broadcaster(store_message_text: bool) -> broadcasters::Model {
+    broadcasters::Model {
+        id: BROADCASTER,
+        display_name: "Broadcaster".into(),
+        profile_image_url: None,
+        store_message_text,
+        message_text_retention_days: None,
+        vote_cooldown_seconds: Some(60),
+        login: Some("broadcaster".into()),
+    }
+}
+
+fn comment(id: u128, second: u32, commenter: i64, text: &str) -> CommentsByVideoAndCursorComment {
+    let mut fragments = vec![json!({ "text": text })];
+    if text.ends_with(' ') {
+        fragments.push(json!({
+            "text": "@Target",
+            "mention": { "id": TARGET.to_string(), "displayName": "Target" },
+        }));
+    }
+
+    serde_json::from_value(json!({
+        "commenter": { "id": commenter.to_string(), "displayName": format!("chatter{commenter}") },
+        "createdAt": format!("2025-04-01T00:00:{second:02}Z"),
+        "id": Uuid::from_u128(id),
+        "message": { "fragments": Value::from(fragments) },
+    }))
+    .expect("comment should parse")
+}
+
+fn rows(store_message_text: bool, opt_outs: &OptOuts) -> CommentRows {
+    let exclusions = Exclusions::from_models(&[excluded_chatters::Model {
+        id: 1,
+        broadcaster_id: None,
+        chatter_id: BOT,
+        reason: None,
+    }]);
+    let comments = vec![
+        comment(1, 1, VOTER, "+2 "),
+        comment(2, 2, VOTER, "+2"),
+        comment(3, 3, VOTER, "hello"),
+        comment(4, 4, BOT, "-2"),
+    ];
+
+    CommentRows::from_comments(
+        comments,
+        &broadcaster(store_message_text),
+        BROADCAST,
+        &Vocabulary::default(),
+        &exclusions,
+        opt_outs,
+        &mut Cooldowns::new(Some(60)),
+    )
+    .unwrap()
+}
+
+#[test]
+fn votes_are_classified_and_suppressed() {
+    let rows = rows(false, &OptOuts::default());
+
+    assert_eq!(
+        rows.messages
+            .iter()
+            .map(|m| (m.id, m.target_chatter_id, m.suppressed))
+            .collect::<Vec<_>>(),
+        [
+            (Uuid::from_u128(1), Some(TARGET), false),
+            (Uuid::from_u128(2), None, true)
+        ]
+    );
+    assert_eq!(
+        rows.chatters
+            .iter()
+            .map(|(chatter, _)| chatter.id)
+            .collect::<Vec<_>>(),
+        [VOTER, TARGET, VOTER]
+    );
+    assert_eq!(rows.roles.len(), 2);
+    assert!(rows.contents.is_empty());
+}
+
+#[test]
+fn only_votes_have_their_text_stored() {
+    let rows = rows(true, &OptOuts::default());
+
+    assert_eq!(
+        rows.contents.iter().map(|c| c.id).collect::<Vec<_>>(),
+        rows.messages.iter().map(|m| m.id).collect::<Vec<_>>()
+    );
+    assert_eq!(rows.contents[0].text, "+2 @Target");
+}
+
+#[test]
+fn opted_out_chatters_are_never_recorded() {
+    let opt_outs =
+        OptOuts::from_models(&[VOTER, BOT].map(|chatter_id| opted_out_chatters::Model {
+            chatter_id,
+            reason: None,
+            opted_out_at: chrono::DateTime::UNIX_EPOCH.naive_utc(),
+        }));
+
+    assert_eq!(rows(true, &opt_outs), CommentRows::default());
+}
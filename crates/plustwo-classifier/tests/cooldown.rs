@@ -0,0 +1,66 @@
+use chrono::{NaiveDate, TimeDelta};
+use plustwo_classifier::Cooldowns;
+use plustwo_database::DateTime;
+
+const ALICE: i64 = 1;
+const BOB: i64 = 2;
+
+fn at(seconds: i64) -> DateTime {
+    NaiveDate::from_ymd_opt(2025, 4, 15)
+        .and_then(|d| d.and_hms_opt(12, 0, 0))
+        .expect("date should be valid")
+        + TimeDelta::seconds(seconds)
+}
+
+#[test]
+fn votes_within_the_window_are_suppressed() {
+    let mut cooldowns = Cooldowns::new(Some(30));
+
+    let corpus = [
+        // The first vote always counts.
+        (ALICE, 0, false),
+        (ALICE, 1, true),
+        (ALICE, 29, true),
+        // Each chatter has their own window.
+        (BOB, 10, false),
+        (BOB, 20, true),
+        // The window ends exactly `window` seconds after the counted vote.
+        (ALICE, 30, false),
+        (ALICE, 59, true),
+        // Suppressed votes don't extend the window.
+        (BOB, 40, false),
+    ];
+
+    for (chatter_id, seconds, expected) in corpus {
+        assert_eq!(
+            cooldowns.suppress(chatter_id, at(seconds)),
+            expected,
+            "vote from {chatter_id} at {seconds}s"
+        );
+    }
+}
+
+#[test]
+fn no_window_never_suppresses() {
+    for window in [None, Some(0), Some(-5)] {
+        let mut cooldowns = Cooldowns::new(window);
+
+        for seconds in 0..5 {
+            assert!(
+                !cooldowns.suppress(ALICE, at(seconds)),
+                "vote at {seconds}s with window {window:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn clearing_forgets_counted_votes() {
+    let mut cooldowns = Cooldowns::new(Some(30));
+
+    assert!(!cooldowns.suppress(ALICE, at(0)));
+    assert!(cooldowns.suppress(ALICE, at(1)));
+
+    cooldowns.clear();
+    assert!(!cooldowns.suppress(ALICE, at(2)));
+}
@@ -0,0 +1,647 @@
+use plustwo_classifier::{
+    Chatter, Fragment, NormalizedMessage, Rule, TargetRule, Vocabulary, classify, classify_with,
+};
+use plustwo_database::entities::{
+    sea_orm_active_enums::{MessageKind, VotePatternKind},
+    vote_patterns,
+};
+
+const SENDER: &str = "1";
+
+fn text(text: &str) -> NormalizedMessage {
+    NormalizedMessage::from_fragments(SENDER, vec![Fragment::text(text)])
+}
+
+fn mention(id: &str, name: &str) -> Fragment {
+    Fragment::Mention {
+        text: format!("@{name}"),
+        chatter: chatter(id, name),
+    }
+}
+
+fn chatter(id: &str, name: &str) -> Chatter {
+    Chatter {
+        id: id.to_string(),
+        display_name: name.to_string(),
+    }
+}
+
+#[test]
+fn kinds() {
+    let corpus: &[(&str, Option<(MessageKind, Rule)>)] = &[
+        // Plain votes.
+        (
+            "+2",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            "-2",
+            Some((MessageKind::MinusTwo, Rule::Prefix("-2".into()))),
+        ),
+        // Votes at the start of a message.
+        (
+            "+2 nice",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            "-2 awful",
+            Some((MessageKind::MinusTwo, Rule::Prefix("-2".into()))),
+        ),
+        (
+            "+2+2+2",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        // Votes at the end of a message.
+        (
+            "nice +2",
+            Some((MessageKind::PlusTwo, Rule::Suffix("+2".into()))),
+        ),
+        (
+            "awful -2",
+            Some((MessageKind::MinusTwo, Rule::Suffix("-2".into()))),
+        ),
+        // Surrounding whitespace is ignored.
+        (
+            "  +2",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            "+2  ",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            "nice +2 ",
+            Some((MessageKind::PlusTwo, Rule::Suffix("+2".into()))),
+        ),
+        (
+            "\t-2\n",
+            Some((MessageKind::MinusTwo, Rule::Prefix("-2".into()))),
+        ),
+        // Plus votes win when both are present.
+        (
+            "+2 -2",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            "-2 +2",
+            Some((MessageKind::PlusTwo, Rule::Suffix("+2".into()))),
+        ),
+        (
+            "-2 and -2",
+            Some((MessageKind::MinusTwo, Rule::Prefix("-2".into()))),
+        ),
+        // Votes in the middle of a message.
+        (
+            "that was +2 honestly",
+            Some((MessageKind::PlusTwo, Rule::Token("+2".into()))),
+        ),
+        (
+            "a -2 b",
+            Some((MessageKind::MinusTwo, Rule::Token("-2".into()))),
+        ),
+        // Neutral votes, which only count without a signed vote.
+        (
+            "+0",
+            Some((MessageKind::PlusZero, Rule::Prefix("+0".into()))),
+        ),
+        (
+            "-0 nice",
+            Some((MessageKind::PlusZero, Rule::Prefix("+0".into()))),
+        ),
+        (
+            "+0 -2",
+            Some((MessageKind::MinusTwo, Rule::Suffix("-2".into()))),
+        ),
+        // Non-votes.
+        ("", None),
+        ("   ", None),
+        ("hello chat", None),
+        ("2", None),
+        ("+", None),
+        ("++", None),
+        ("L", None),
+    ];
+
+    for (input, expected) in corpus {
+        let actual = classify(&text(input)).map(|c| (c.kind, c.rule));
+        assert_eq!(&actual, expected, "classifying {input:?}");
+    }
+}
+
+#[test]
+fn spellings() {
+    let corpus: &[(&str, Option<(MessageKind, Rule)>)] = &[
+        // Spaced, trailing and full-width signs.
+        (
+            "+ 2",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            "2+",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            " 2- ",
+            Some((MessageKind::MinusTwo, Rule::Prefix("-2".into()))),
+        ),
+        (
+            "＋２",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            "+２",
+            Some((MessageKind::PlusTwo, Rule::Prefix("+2".into()))),
+        ),
+        (
+            "\u{2212}2",
+            Some((MessageKind::MinusTwo, Rule::Prefix("-2".into()))),
+        ),
+        // Votes must stand on their own.
+        ("that was great+2", None),
+        ("covid-19", None),
+        ("it's 1-2 now", None),
+        ("2025-04-15", None),
+        ("2+2", None),
+        ("+2x", None),
+        // Trailing signs only count on their own, since they usually mean "or more".
+        ("nice 2-", None),
+        ("18+ only", None),
+        ("top 5+", None),
+        // Signs after a plain number are arithmetic.
+        ("10 - 2", None),
+        ("10 +2", None),
+    ];
+
+    for (input, expected) in corpus {
+        let actual = classify(&text(input)).map(|c| (c.kind, c.rule));
+        assert_eq!(&actual, expected, "classifying {input:?}");
+    }
+}
+
+#[test]
+fn magnitudes() {
+    let corpus: &[(&str, Option<(MessageKind, i32)>)] = &[
+        ("+2", Some((MessageKind::PlusTwo, 2))),
+        ("-2", Some((MessageKind::MinusTwo, -2))),
+        ("+1", Some((MessageKind::PlusTwo, 1))),
+        ("-1", Some((MessageKind::MinusTwo, -1))),
+        ("+5 nice", Some((MessageKind::PlusTwo, 5))),
+        ("awful -10", Some((MessageKind::MinusTwo, -10))),
+        ("+1000", Some((MessageKind::PlusTwo, 1000))),
+        ("+20", Some((MessageKind::PlusTwo, 20))),
+        ("+007", Some((MessageKind::PlusTwo, 7))),
+        // Plus votes still win when both are present.
+        ("-10 +1", Some((MessageKind::PlusTwo, 1))),
+        // Numbers are read whole, rather than as a +2 or -2 followed by more digits, and anything
+        // bigger than a vote can be isn't one.
+        ("-2000 ping", None),
+        ("+1001", None),
+        ("+99999999999", None),
+        ("-99999999999", None),
+        ("２０+", Some((MessageKind::PlusTwo, 20))),
+        // Only the first number of the winning sign counts.
+        ("+2+2+2", Some((MessageKind::PlusTwo, 2))),
+        ("+3 then +4", Some((MessageKind::PlusTwo, 3))),
+        ("é -4", Some((MessageKind::MinusTwo, -4))),
+        ("é-4", None),
+        ("é4", None),
+    ];
+
+    for (input, expected) in corpus {
+        let actual = classify(&text(input)).map(|c| (c.kind, c.value));
+        assert_eq!(&actual, expected, "classifying {input:?}");
+    }
+}
+
+const BROADCASTER: i64 = 10;
+
+/// Global `GG`/`RIP` literals, with a regex and an emote for `BROADCASTER` and a literal for
+/// another broadcaster.
+fn vocabulary() -> Vocabulary {
+    let pattern =
+        |id, broadcaster_id, pattern_kind, pattern: &str, message_kind| vote_patterns::Model {
+            id,
+            broadcaster_id,
+            pattern_kind,
+            pattern: pattern.to_string(),
+            message_kind,
+        };
+
+    Vocabulary::from_patterns(&[
+        pattern(
+            1,
+            None,
+            VotePatternKind::Literal,
+            "GG",
+            MessageKind::PlusTwo,
+        ),
+        pattern(
+            2,
+            None,
+            VotePatternKind::Literal,
+            "RIP",
+            MessageKind::MinusTwo,
+        ),
+        pattern(
+            3,
+            Some(BROADCASTER),
+            VotePatternKind::Regex,
+            "^[WL]$",
+            MessageKind::PlusTwo,
+        ),
+        pattern(
+            4,
+            Some(BROADCASTER),
+            VotePatternKind::Emote,
+            "25",
+            MessageKind::MinusTwo,
+        ),
+        pattern(
+            5,
+            Some(BROADCASTER + 1),
+            VotePatternKind::Literal,
+            "o7",
+            MessageKind::PlusTwo,
+        ),
+    ])
+    .expect("patterns should be valid")
+}
+
+fn emote(id: &str, name: &str) -> Fragment {
+    Fragment::Emote {
+        text: name.to_string(),
+        emote_id: id.to_string(),
+    }
+}
+
+#[test]
+fn vocabularies() {
+    type Expected = Option<(MessageKind, Rule)>;
+
+    let vocabulary = vocabulary();
+
+    let kappa = NormalizedMessage::from_fragments(
+        SENDER,
+        vec![Fragment::text("nice "), emote("25", "Kappa")],
+    );
+
+    let corpus: &[(i64, NormalizedMessage, Expected)] = &[
+        // Global patterns apply to every broadcaster.
+        (
+            BROADCASTER,
+            text("GG"),
+            Some((MessageKind::PlusTwo, Rule::Prefix("GG".into()))),
+        ),
+        (
+            BROADCASTER + 1,
+            text("nice RIP"),
+            Some((MessageKind::MinusTwo, Rule::Suffix("RIP".into()))),
+        ),
+        // Broadcaster patterns only apply to their broadcaster.
+        (
+            BROADCASTER,
+            text("W"),
+            Some((MessageKind::PlusTwo, Rule::Regex("^[WL]$".into()))),
+        ),
+        (BROADCASTER, text("W chat"), None),
+        (BROADCASTER + 1, text("W"), None),
+        (
+            BROADCASTER + 1,
+            text("o7"),
+            Some((MessageKind::PlusTwo, Rule::Prefix("o7".into()))),
+        ),
+        (BROADCASTER, text("o7"), None),
+        // Emotes are matched by ID, not by their text.
+        (
+            BROADCASTER,
+            kappa.clone(),
+            Some((MessageKind::MinusTwo, Rule::Emote("25".into()))),
+        ),
+        (BROADCASTER, text("nice Kappa"), None),
+        (BROADCASTER + 1, kappa, None),
+        // Broadcaster patterns are checked before global patterns.
+        (
+            BROADCASTER,
+            NormalizedMessage::from_fragments(
+                SENDER,
+                vec![Fragment::text("GG "), emote("25", "Kappa")],
+            ),
+            Some((MessageKind::MinusTwo, Rule::Emote("25".into()))),
+        ),
+        // Numbers are checked before any pattern.
+        (
+            BROADCASTER,
+            NormalizedMessage::from_fragments(
+                SENDER,
+                vec![Fragment::text("+5 "), emote("25", "Kappa")],
+            ),
+            Some((MessageKind::PlusTwo, Rule::Prefix("+5".into()))),
+        ),
+    ];
+
+    for (broadcaster_id, message, expected) in corpus {
+        let actual = classify_with(&vocabulary, *broadcaster_id, message).map(|c| (c.kind, c.rule));
+        assert_eq!(
+            &actual, expected,
+            "classifying {message:?} for {broadcaster_id}"
+        );
+    }
+}
+
+#[test]
+fn literals_match_whole_words() {
+    let pattern = |id, pattern: &str, message_kind| vote_patterns::Model {
+        id,
+        broadcaster_id: None,
+        pattern_kind: VotePatternKind::Literal,
+        pattern: pattern.to_string(),
+        message_kind,
+    };
+    let vocabulary = Vocabulary::from_patterns(&[
+        pattern(1, "W", MessageKind::W),
+        pattern(2, "L", MessageKind::L),
+        pattern(3, "RIP", MessageKind::MinusTwo),
+        // Patterns are normalized like messages are.
+        pattern(4, "ｏ７", MessageKind::PlusTwo),
+    ])
+    .expect("patterns should be valid");
+
+    let corpus: &[(&str, Option<(MessageKind, Rule)>)] = &[
+        ("W", Some((MessageKind::W, Rule::Prefix("W".into())))),
+        ("W chat", Some((MessageKind::W, Rule::Prefix("W".into())))),
+        ("huge W", Some((MessageKind::W, Rule::Suffix("W".into())))),
+        ("L!", Some((MessageKind::L, Rule::Prefix("L".into())))),
+        ("LUL L", Some((MessageKind::L, Rule::Suffix("L".into())))),
+        (
+            "RIP",
+            Some((MessageKind::MinusTwo, Rule::Prefix("RIP".into()))),
+        ),
+        (
+            "o7",
+            Some((MessageKind::PlusTwo, Rule::Prefix("o7".into()))),
+        ),
+        (
+            "ｏ７ chat",
+            Some((MessageKind::PlusTwo, Rule::Prefix("o7".into()))),
+        ),
+        ("LUL", None),
+        ("Let's go", None),
+        ("What a play", None),
+        ("TRIP", None),
+        ("a trip to RIPley's", None),
+    ];
+
+    for (input, expected) in corpus {
+        let actual =
+            classify_with(&vocabulary, BROADCASTER, &text(input)).map(|c| (c.kind, c.rule));
+        assert_eq!(&actual, expected, "classifying {input:?}");
+    }
+}
+
+#[test]
+fn invalid_patterns_are_rejected() {
+    let error = Vocabulary::from_patterns(&[vote_patterns::Model {
+        id: 7,
+        broadcaster_id: None,
+        pattern_kind: VotePatternKind::Regex,
+        pattern: "(".to_string(),
+        message_kind: MessageKind::PlusTwo,
+    }])
+    .expect_err("pattern should be invalid");
+
+    assert_eq!(error.id, 7);
+}
+
+#[test]
+fn pattern_kinds_have_default_values() {
+    let pattern = |id, pattern: &str, message_kind| vote_patterns::Model {
+        id,
+        broadcaster_id: None,
+        pattern_kind: VotePatternKind::Regex,
+        pattern: pattern.to_string(),
+        message_kind,
+    };
+    let vocabulary = Vocabulary::from_patterns(&[
+        pattern(1, "^W$", MessageKind::W),
+        pattern(2, "^L$", MessageKind::L),
+        pattern(3, "^meh$", MessageKind::PlusZero),
+    ])
+    .expect("patterns should be valid");
+
+    let corpus = [
+        ("W", MessageKind::W, 2),
+        ("L", MessageKind::L, -2),
+        ("meh", MessageKind::PlusZero, 0),
+    ];
+
+    for (input, kind, value) in corpus {
+        let actual =
+            classify_with(&vocabulary, BROADCASTER, &text(input)).map(|c| (c.kind, c.value));
+        assert_eq!(actual, Some((kind, value)), "classifying {input:?}");
+    }
+}
+
+#[test]
+fn fragments_are_joined() {
+    let corpus: &[(&[&str], Option<MessageKind>)] = &[
+        (&["+", "2"], Some(MessageKind::PlusTwo)),
+        (&["Kappa", " +2"], Some(MessageKind::PlusTwo)),
+        (&["-2 ", "Kappa"], Some(MessageKind::MinusTwo)),
+        (&["Kappa", " -2 ", "Kappa"], Some(MessageKind::MinusTwo)),
+        (&["Kappa", "-2", "Kappa"], None),
+        (&[], None),
+    ];
+
+    for (fragments, expected) in corpus {
+        let message = NormalizedMessage::from_fragments(
+            SENDER,
+            fragments.iter().copied().map(Fragment::text).collect(),
+        );
+
+        assert_eq!(
+            &classify(&message).map(|c| c.kind),
+            expected,
+            "classifying {fragments:?}"
+        );
+    }
+}
+
+#[test]
+fn emotes_are_not_text() {
+    let corpus: &[(&[Fragment], Option<MessageKind>)] = &[
+        (&[Fragment::text("gg "), emote("1", "+2")], None),
+        (&[emote("1", "-2"), Fragment::text(" nice")], None),
+        (
+            &[emote("1", "Kappa"), Fragment::text(" +2")],
+            Some(MessageKind::PlusTwo),
+        ),
+        (
+            &[Fragment::text("-2 "), emote("1", "Kappa")],
+            Some(MessageKind::MinusTwo),
+        ),
+        // Emotes split the text around them, rather than joining it.
+        (
+            &[
+                Fragment::text("+"),
+                emote("1", "Kappa"),
+                Fragment::text("2"),
+            ],
+            None,
+        ),
+        (
+            &[
+                Fragment::text("gg "),
+                Fragment::Cheermote {
+                    text: "Cheer-2".to_string(),
+                    prefix: "Cheer".to_string(),
+                    bits: 2,
+                },
+            ],
+            None,
+        ),
+    ];
+
+    for (fragments, expected) in corpus {
+        let message = NormalizedMessage::from_fragments(SENDER, fragments.to_vec());
+
+        assert_eq!(
+            &classify(&message).map(|c| c.kind),
+            expected,
+            "classifying {fragments:?}"
+        );
+    }
+}
+
+#[test]
+fn stored_fragments() {
+    let fragments = vec![
+        Fragment::text("+2 "),
+        mention("2", "Bob"),
+        emote("25", "Kappa"),
+        Fragment::Cheermote {
+            text: "Cheer100".to_string(),
+            prefix: "Cheer".to_string(),
+            bits: 100,
+        },
+    ];
+
+    let stored = serde_json::to_value(&fragments).expect("fragments should serialize");
+    assert_eq!(
+        serde_json::from_value::<Vec<Fragment>>(stored).expect("fragments should deserialize"),
+        fragments
+    );
+
+    // Fragments stored before they had kinds are still readable.
+    let legacy = serde_json::json!([
+        { "text": "+2 " },
+        { "text": "@Bob", "mention": { "id": "2", "displayName": "Bob" } },
+        { "text": "Kappa", "emoteId": "25" },
+    ]);
+    assert_eq!(
+        serde_json::from_value::<Vec<Fragment>>(legacy).expect("fragments should deserialize"),
+        fragments[..3]
+    );
+}
+
+#[test]
+fn targets() {
+    let bob = chatter("2", "Bob");
+    let eve = chatter("3", "Eve");
+
+    let corpus: Vec<(NormalizedMessage, Option<(&Chatter, TargetRule)>)> = vec![
+        // Untargeted votes.
+        (text("+2"), None),
+        // Mentions.
+        (
+            NormalizedMessage::from_fragments(
+                SENDER,
+                vec![Fragment::text("+2 "), mention("2", "Bob")],
+            ),
+            Some((&bob, TargetRule::Mention)),
+        ),
+        (
+            NormalizedMessage::from_fragments(
+                SENDER,
+                vec![mention("2", "Bob"), Fragment::text(" -2")],
+            ),
+            Some((&bob, TargetRule::Mention)),
+        ),
+        // Only the first mention counts.
+        (
+            NormalizedMessage::from_fragments(
+                SENDER,
+                vec![
+                    Fragment::text("+2 "),
+                    mention("3", "Eve"),
+                    Fragment::text(" "),
+                    mention("2", "Bob"),
+                ],
+            ),
+            Some((&eve, TargetRule::Mention)),
+        ),
+        // Replies take priority over mentions.
+        (
+            NormalizedMessage {
+                reply_to: Some(bob.clone()),
+                ..NormalizedMessage::from_fragments(
+                    SENDER,
+                    vec![Fragment::text("+2 "), mention("3", "Eve")],
+                )
+            },
+            Some((&bob, TargetRule::Reply)),
+        ),
+        // Voting for yourself doesn't count as a target.
+        (
+            NormalizedMessage::from_fragments(
+                SENDER,
+                vec![Fragment::text("+2 "), mention(SENDER, "Me")],
+            ),
+            None,
+        ),
+        (
+            NormalizedMessage {
+                reply_to: Some(chatter(SENDER, "Me")),
+                ..text("+2")
+            },
+            None,
+        ),
+    ];
+
+    for (message, expected) in corpus {
+        let classification = classify(&message).expect("message should be a vote");
+        let actual = classification.target.as_ref().map(|t| (&t.chatter, t.rule));
+
+        assert_eq!(actual, expected, "targeting {message:?}");
+    }
+}
+
+#[test]
+fn non_votes_have_no_target() {
+    let message =
+        NormalizedMessage::from_fragments(SENDER, vec![Fragment::text("hi "), mention("2", "Bob")]);
+
+    assert_eq!(classify(&message), None);
+}
+
+#[test]
+fn explanations() {
+    let corpus = [
+        (text("+2"), "PlusTwo: message starts with \"+2\""),
+        (text("nice -2"), "MinusTwo: message ends with \"-2\""),
+        (text("a + 2 b"), "PlusTwo: message contains \"+2\""),
+        (
+            NormalizedMessage::from_fragments(
+                SENDER,
+                vec![Fragment::text("+2 "), mention("2", "Bob")],
+            ),
+            "PlusTwo: message starts with \"+2\", aimed at the first mentioned chatter",
+        ),
+    ];
+
+    for (message, expected) in corpus {
+        assert_eq!(
+            classify(&message).map(|c| c.explain()).as_deref(),
+            Some(expected)
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use plustwo_classifier::{Exclusions, OptOuts};
+use plustwo_database::entities::{excluded_chatters, opted_out_chatters};
+
+const BROADCASTER: i64 = 10;
+const NIGHTBOT: i64 = 19_264_788;
+const MODBOT: i64 = 20;
+const CHATTER: i64 = 30;
+
+#[test]
+fn exclusions() {
+    let exclusion = |id, broadcaster_id, chatter_id| excluded_chatters::Model {
+        id,
+        broadcaster_id,
+        chatter_id,
+        reason: None,
+    };
+    let exclusions = Exclusions::from_models(&[
+        exclusion(1, None, NIGHTBOT),
+        exclusion(2, Some(BROADCASTER), MODBOT),
+    ]);
+
+    let corpus = [
+        // Global exclusions apply everywhere.
+        (BROADCASTER, NIGHTBOT, true),
+        (BROADCASTER + 1, NIGHTBOT, true),
+        // Broadcaster exclusions only apply to their broadcaster.
+        (BROADCASTER, MODBOT, true),
+        (BROADCASTER + 1, MODBOT, false),
+        // Broadcasters are always excluded from their own chat.
+        (BROADCASTER, BROADCASTER, true),
+        (BROADCASTER + 1, BROADCASTER, false),
+        (BROADCASTER, CHATTER, false),
+    ];
+
+    for (broadcaster_id, chatter_id, expected) in corpus {
+        assert_eq!(
+            exclusions.is_excluded(broadcaster_id, chatter_id),
+            expected,
+            "{chatter_id} in {broadcaster_id}'s chat"
+        );
+    }
+}
+
+#[test]
+fn opt_outs() {
+    let opt_outs = OptOuts::from_models(&[opted_out_chatters::Model {
+        chatter_id: CHATTER,
+        reason: None,
+        opted_out_at: chrono::DateTime::UNIX_EPOCH.naive_utc(),
+    }]);
+
+    assert!(opt_outs.is_opted_out(CHATTER));
+    assert!(!opt_outs.is_opted_out(NIGHTBOT));
+    assert!(!OptOuts::default().is_opted_out(CHATTER));
+}
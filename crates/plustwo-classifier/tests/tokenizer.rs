@@ -0,0 +1,117 @@
+use plustwo_classifier::{
+    Fragment, NormalizedMessage, classify,
+    tokenizer::{self, MAX_MAGNITUDE, normalize, tokenize},
+};
+use plustwo_database::entities::sea_orm_active_enums::MessageKind;
+use proptest::prelude::*;
+
+fn text(text: &str) -> NormalizedMessage {
+    NormalizedMessage::from_fragments("1", vec![Fragment::text(text)])
+}
+
+/// Converts printable ASCII to its full-width form, like "+2" to "＋２".
+fn full_width(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '!'..='~' => char::from_u32(u32::from(c) + 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// A vote token in any of its spellings, along with its value. Trailing signs are only included
+/// if `trailing` is set, since they only count on their own.
+fn vote(trailing: bool) -> impl Strategy<Value = (String, i32)> {
+    let spellings = if trailing { 0..4_usize } else { 0..3 };
+    (1..=MAX_MAGNITUDE, any::<bool>(), spellings).prop_map(|(magnitude, negative, spelling)| {
+        let sign = if negative { '-' } else { '+' };
+        let value = if negative { -magnitude } else { magnitude };
+        let token = match spelling {
+            0 => format!("{sign}{magnitude}"),
+            1 => format!("{sign} {magnitude}"),
+            2 => full_width(&format!("{sign}{magnitude}")),
+            _ => format!("{magnitude}{sign}"),
+        };
+
+        (token, value)
+    })
+}
+
+/// Words without any digits or signs, which can never be votes themselves.
+fn words() -> impl Strategy<Value = String> {
+    prop::collection::vec("[a-zA-Zé]{1,8}", 0..4).prop_map(|words| words.join(" "))
+}
+
+proptest! {
+    #[test]
+    fn normalizing_is_idempotent(input in any::<String>()) {
+        let once = normalize(&input);
+        prop_assert_eq!(normalize(&once), once);
+    }
+
+    #[test]
+    fn full_width_matches_ascii(input in "[ -~]{0,16}") {
+        prop_assert_eq!(classify(&text(&full_width(&input))), classify(&text(&input)));
+    }
+
+    #[test]
+    fn classifying_never_panics(input in any::<String>()) {
+        let _ = classify(&text(&input));
+    }
+
+    #[test]
+    fn votes_are_found_anywhere((token, value) in vote(false), before in words(), after in words()) {
+        let classification = classify(&text(&format!("{before} {token} {after}")));
+
+        prop_assert_eq!(classification.map(|c| c.value), Some(value));
+    }
+
+    #[test]
+    fn votes_inside_words_are_ignored(
+        before in "[a-z]{1,8}",
+        sign in "[+-]",
+        magnitude in 1..=i32::MAX,
+        after in "[a-z]{0,8}",
+    ) {
+        let token = format!("{before}{sign}{magnitude}{after}");
+        prop_assert_eq!(tokenize(&token), vec![]);
+    }
+
+    #[test]
+    fn tokens_lie_within_the_text(input in any::<String>()) {
+        let normalized = normalize(&input);
+        for token in tokenize(&normalized) {
+            prop_assert!(token.start < token.end);
+            prop_assert!(normalized.get(token.start..token.end).is_some());
+        }
+    }
+
+    #[test]
+    fn kind_follows_sign((token, value) in vote(true)) {
+        let kind = classify(&text(&token)).map(|c| c.kind);
+        let expected = if value > 0 { MessageKind::PlusTwo } else { MessageKind::MinusTwo };
+
+        prop_assert_eq!(kind, Some(expected));
+    }
+}
+
+#[test]
+fn sign_conflicts() {
+    let corpus = [
+        // The first positive vote wins.
+        ("+0 -2 then +3 then -4 +0", Some(3)),
+        // Votes of a single sign are never in conflict.
+        ("-2 -3", Some(-2)),
+        // Neutral votes only count when there's nothing else.
+        ("+0 -2", Some(-2)),
+        ("+0", Some(0)),
+    ];
+
+    for (input, expected) in corpus {
+        assert_eq!(
+            tokenizer::find(input).map(|(_, value, _)| value),
+            expected,
+            "resolving {input:?}"
+        );
+    }
+}
@@ -5,6 +5,22 @@ mod m20250313_000002_create_broadcasts_table;
 mod m20250313_000003_create_chatters_table;
 mod m20250313_000004_create_message_kind_type;
 mod m20250313_000005_create_messages_table;
+mod m20250401_000006_add_message_text_settings_to_broadcasters;
+mod m20250401_000007_create_message_contents_table;
+mod m20250401_000008_create_message_roles_table;
+mod m20250401_000009_add_target_chatter_to_messages;
+mod m20250408_000010_create_table_versions_table;
+mod m20250408_000011_create_vote_patterns_table;
+mod m20250408_000012_add_value_to_messages;
+mod m20250415_000013_add_vote_cooldowns;
+mod m20250415_000014_create_excluded_chatters_table;
+mod m20250422_000015_add_message_kinds;
+mod m20250422_000016_create_query_indexes;
+mod m20250429_000017_create_broadcast_minute_stats_table;
+mod m20250429_000018_create_chatter_names_table;
+mod m20250506_000019_add_login_to_broadcasters;
+mod m20250513_000020_create_chatter_erasures_table;
+mod m20250520_000021_create_opted_out_chatters_table;
 
 pub struct Migrator;
 
@@ -17,6 +33,22 @@ impl MigratorTrait for Migrator {
             Box::new(m20250313_000003_create_chatters_table::Migration),
             Box::new(m20250313_000004_create_message_kind_type::Migration),
             Box::new(m20250313_000005_create_messages_table::Migration),
+            Box::new(m20250401_000006_add_message_text_settings_to_broadcasters::Migration),
+            Box::new(m20250401_000007_create_message_contents_table::Migration),
+            Box::new(m20250401_000008_create_message_roles_table::Migration),
+            Box::new(m20250401_000009_add_target_chatter_to_messages::Migration),
+            Box::new(m20250408_000010_create_table_versions_table::Migration),
+            Box::new(m20250408_000011_create_vote_patterns_table::Migration),
+            Box::new(m20250408_000012_add_value_to_messages::Migration),
+            Box::new(m20250415_000013_add_vote_cooldowns::Migration),
+            Box::new(m20250415_000014_create_excluded_chatters_table::Migration),
+            Box::new(m20250422_000015_add_message_kinds::Migration),
+            Box::new(m20250422_000016_create_query_indexes::Migration),
+            Box::new(m20250429_000017_create_broadcast_minute_stats_table::Migration),
+            Box::new(m20250429_000018_create_chatter_names_table::Migration),
+            Box::new(m20250506_000019_add_login_to_broadcasters::Migration),
+            Box::new(m20250513_000020_create_chatter_erasures_table::Migration),
+            Box::new(m20250520_000021_create_opted_out_chatters_table::Migration),
         ]
     }
 }
@@ -1,5 +1,5 @@
 use extension::postgres::Type;
-use sea_orm_migration::prelude::*;
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
 
 pub struct Migration;
 
@@ -12,6 +12,11 @@ impl MigrationName for Migration {
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Only Postgres has enum types. Elsewhere, message kinds are stored as text.
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
         manager
             .create_type(
                 Type::create()
@@ -23,12 +28,17 @@ impl MigrationTrait for Migration {
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
         manager
             .drop_type(Type::drop().name(MessageKind::MessageKind).to_owned())
             .await
     }
 }
 
+#[allow(clippy::enum_variant_names)]
 pub enum MessageKind {
     MessageKind,
     PlusTwo,
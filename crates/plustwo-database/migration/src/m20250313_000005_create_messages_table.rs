@@ -31,7 +31,10 @@ impl MigrationTrait for Migration {
                     .col(ColumnDef::new(Messages::SentAt).timestamp().not_null())
                     .col(
                         ColumnDef::new(Messages::MessageKind)
-                            .custom(MessageKind::MessageKind)
+                            .enumeration(
+                                MessageKind::MessageKind,
+                                [MessageKind::PlusTwo, MessageKind::MinusTwo],
+                            )
                             .not_null(),
                     )
                     .foreign_key(
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250401_000006_add_message_text_settings_to_broadcasters"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only takes a single change per `ALTER TABLE`.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Broadcasters::Table)
+                    .add_column(
+                        ColumnDef::new(Broadcasters::StoreMessageText)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Broadcasters::Table)
+                    .add_column(
+                        ColumnDef::new(Broadcasters::MessageTextRetentionDays)
+                            .integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Broadcasters::Table)
+                    .drop_column(Broadcasters::StoreMessageText)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Broadcasters::Table)
+                    .drop_column(Broadcasters::MessageTextRetentionDays)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Broadcasters {
+    Table,
+    StoreMessageText,
+    MessageTextRetentionDays,
+}
@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250313_000002_create_broadcasts_table::Broadcasts,
+    m20250313_000003_create_chatters_table::Chatters,
+    m20250313_000005_create_messages_table::Messages,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250401_000007_create_message_contents_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Only votes have their text stored, so contents are removed along with their vote.
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessageContents::Table)
+                    .col(
+                        ColumnDef::new(MessageContents::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MessageContents::BroadcastId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MessageContents::ChatterId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MessageContents::SentAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MessageContents::Text).text().not_null())
+                    .col(
+                        ColumnDef::new(MessageContents::Fragments)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MessageContents::ReplyToChatterId).big_integer())
+                    .col(ColumnDef::new(MessageContents::ReplyToDisplayName).string())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-message-contents-id")
+                            .from(MessageContents::Table, MessageContents::Id)
+                            .to(Messages::Table, Messages::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-message-contents-broadcast-id")
+                            .from(MessageContents::Table, MessageContents::BroadcastId)
+                            .to(Broadcasts::Table, Broadcasts::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-message-contents-chatter-id")
+                            .from(MessageContents::Table, MessageContents::ChatterId)
+                            .to(Chatters::Table, Chatters::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MessageContents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum MessageContents {
+    Table,
+
+    Id,
+    BroadcastId,
+    ChatterId,
+
+    SentAt,
+
+    Text,
+    Fragments,
+
+    ReplyToChatterId,
+    ReplyToDisplayName,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250401_000008_create_message_roles_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessageRoles::Table)
+                    .col(
+                        ColumnDef::new(MessageRoles::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MessageRoles::IsSubscriber)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MessageRoles::IsModerator)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MessageRoles::IsVip).boolean().not_null())
+                    .col(ColumnDef::new(MessageRoles::IsFounder).boolean().not_null())
+                    .col(ColumnDef::new(MessageRoles::SubscriberMonths).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MessageRoles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum MessageRoles {
+    Table,
+
+    Id,
+
+    IsSubscriber,
+    IsModerator,
+    IsVip,
+    IsFounder,
+    SubscriberMonths,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+use crate::m20250313_000003_create_chatters_table::Chatters;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250401_000009_add_target_chatter_to_messages"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let column = ColumnDef::new(Messages::TargetChatterId)
+            .big_integer()
+            .to_owned();
+
+        // SQLite can't add constraints to an existing table, so the column goes unchecked there.
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return manager
+                .alter_table(
+                    Table::alter()
+                        .table(Messages::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .add_column(column)
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-target-chatter-id")
+                            .from_tbl(Messages::Table)
+                            .from_col(Messages::TargetChatterId)
+                            .to_tbl(Chatters::Table)
+                            .to_col(Chatters::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return manager
+                .alter_table(
+                    Table::alter()
+                        .table(Messages::Table)
+                        .drop_column(Messages::TargetChatterId)
+                        .to_owned(),
+                )
+                .await;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .drop_foreign_key(Alias::new("fk-target-chatter-id"))
+                    .drop_column(Messages::TargetChatterId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Messages {
+    Table,
+    TargetChatterId,
+}
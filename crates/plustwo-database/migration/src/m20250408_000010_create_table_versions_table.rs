@@ -0,0 +1,113 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250408_000010_create_table_versions_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TableVersions::Table)
+                    .col(
+                        ColumnDef::new(TableVersions::TableName)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TableVersions::Version)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Bumps the version of whichever table the trigger is attached to, so that clients can
+        // cheaply check whether their cached copy is out of date. SQLite has no trigger
+        // functions, so each of its triggers bumps the version itself.
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r"
+                CREATE FUNCTION bump_table_version() RETURNS trigger AS $$
+                BEGIN
+                    INSERT INTO table_versions (table_name, version) VALUES (TG_TABLE_NAME, 1)
+                    ON CONFLICT (table_name)
+                    DO UPDATE SET version = table_versions.version + 1;
+                    RETURN NULL;
+                END;
+                $$ LANGUAGE plpgsql;
+                ",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DbBackend::Postgres {
+            manager
+                .get_connection()
+                .execute_unprepared("DROP FUNCTION bump_table_version")
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(TableVersions::Table).to_owned())
+            .await
+    }
+}
+
+/// Bumps the version of `table` whenever it changes. The triggers are dropped along with the
+/// table.
+pub async fn track_table_version(manager: &SchemaManager<'_>, table: &str) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    if manager.get_database_backend() == DbBackend::Postgres {
+        db.execute_unprepared(&format!(
+            "CREATE TRIGGER {table}_version
+            AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON {table}
+            FOR EACH STATEMENT EXECUTE FUNCTION bump_table_version();"
+        ))
+        .await?;
+
+        return Ok(());
+    }
+
+    // SQLite only has row triggers, with one event each. Bumping once per row still tells
+    // clients that the table changed.
+    for event in ["INSERT", "UPDATE", "DELETE"] {
+        db.execute_unprepared(&format!(
+            "CREATE TRIGGER {table}_version_{suffix}
+            AFTER {event} ON {table}
+            BEGIN
+                INSERT INTO table_versions (table_name, version) VALUES ('{table}', 1)
+                ON CONFLICT (table_name)
+                DO UPDATE SET version = table_versions.version + 1;
+            END;",
+            suffix = event.to_lowercase(),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Iden)]
+pub enum TableVersions {
+    Table,
+    TableName,
+    Version,
+}
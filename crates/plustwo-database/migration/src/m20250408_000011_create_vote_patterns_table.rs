@@ -0,0 +1,153 @@
+use extension::postgres::Type;
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+use crate::{
+    m20250313_000001_create_broadcasters_table::Broadcasters,
+    m20250313_000004_create_message_kind_type::MessageKind,
+    m20250408_000010_create_table_versions_table::track_table_version,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250408_000011_create_vote_patterns_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let kinds = [
+            VotePatternKind::Literal,
+            VotePatternKind::Regex,
+            VotePatternKind::Emote,
+        ];
+        if manager.get_database_backend() == DbBackend::Postgres {
+            manager
+                .create_type(
+                    Type::create()
+                        .as_enum(VotePatternKind::VotePatternKind)
+                        .values(kinds)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(VotePatterns::Table)
+                    .col(
+                        ColumnDef::new(VotePatterns::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(VotePatterns::BroadcasterId).big_integer())
+                    .col(
+                        ColumnDef::new(VotePatterns::PatternKind)
+                            .enumeration(VotePatternKind::VotePatternKind, kinds)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(VotePatterns::Pattern).string().not_null())
+                    .col(
+                        ColumnDef::new(VotePatterns::MessageKind)
+                            .enumeration(
+                                MessageKind::MessageKind,
+                                [MessageKind::PlusTwo, MessageKind::MinusTwo],
+                            )
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-vote-patterns-broadcaster-id")
+                            .from(VotePatterns::Table, VotePatterns::BroadcasterId)
+                            .to(Broadcasters::Table, Broadcasters::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        track_table_version(manager, "vote_patterns").await?;
+
+        // The global defaults match the previously hardcoded behaviour.
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(VotePatterns::Table)
+                    .columns([
+                        VotePatterns::PatternKind,
+                        VotePatterns::Pattern,
+                        VotePatterns::MessageKind,
+                    ])
+                    .values_panic([
+                        Expr::val("literal").as_enum(VotePatternKind::VotePatternKind),
+                        "+2".into(),
+                        Expr::val("plus_two").as_enum(MessageKind::MessageKind),
+                    ])
+                    .values_panic([
+                        Expr::val("literal").as_enum(VotePatternKind::VotePatternKind),
+                        "-2".into(),
+                        Expr::val("minus_two").as_enum(MessageKind::MessageKind),
+                    ])
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VotePatterns::Table).to_owned())
+            .await?;
+
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .name(VotePatternKind::VotePatternKind)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum VotePatterns {
+    Table,
+
+    Id,
+    BroadcasterId,
+
+    PatternKind,
+    Pattern,
+    MessageKind,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy)]
+pub enum VotePatternKind {
+    VotePatternKind,
+    Literal,
+    Regex,
+    Emote,
+}
+impl Iden for VotePatternKind {
+    fn unquoted(&self, s: &mut dyn std::fmt::Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                VotePatternKind::VotePatternKind => "vote_pattern_kind",
+                VotePatternKind::Literal => "literal",
+                VotePatternKind::Regex => "regex",
+                VotePatternKind::Emote => "emote",
+            }
+        )
+        .unwrap();
+    }
+}
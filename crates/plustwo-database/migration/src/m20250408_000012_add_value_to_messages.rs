@@ -0,0 +1,112 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+use crate::{
+    m20250313_000004_create_message_kind_type::MessageKind,
+    m20250408_000011_create_vote_patterns_table::{VotePatternKind, VotePatterns},
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250408_000012_add_value_to_messages"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Every existing message is either a +2 or a -2, so the default covers the former and
+        // the latter are backfilled.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .add_column(
+                        ColumnDef::new(Messages::Value)
+                            .integer()
+                            .not_null()
+                            .default(2),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Messages::Table)
+                    .value(Messages::Value, -2)
+                    .and_where(
+                        Expr::col(Messages::MessageKind)
+                            .eq(Expr::val("minus_two").as_enum(MessageKind::MessageKind)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite can't change a column's default, so it keeps the default there.
+        if manager.get_database_backend() == DbBackend::Postgres {
+            manager
+                .get_connection()
+                .execute_unprepared("ALTER TABLE messages ALTER COLUMN value DROP DEFAULT")
+                .await?;
+        }
+
+        // Numeric votes are recognised without a pattern, so the seeded defaults are redundant.
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(VotePatterns::Table)
+                    .and_where(Expr::col(VotePatterns::BroadcasterId).is_null())
+                    .and_where(
+                        Expr::col(VotePatterns::PatternKind)
+                            .eq(Expr::val("literal").as_enum(VotePatternKind::VotePatternKind)),
+                    )
+                    .and_where(Expr::col(VotePatterns::Pattern).is_in(["+2", "-2"]))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(VotePatterns::Table)
+                    .columns([
+                        VotePatterns::PatternKind,
+                        VotePatterns::Pattern,
+                        VotePatterns::MessageKind,
+                    ])
+                    .values_panic([
+                        Expr::val("literal").as_enum(VotePatternKind::VotePatternKind),
+                        "+2".into(),
+                        Expr::val("plus_two").as_enum(MessageKind::MessageKind),
+                    ])
+                    .values_panic([
+                        Expr::val("literal").as_enum(VotePatternKind::VotePatternKind),
+                        "-2".into(),
+                        Expr::val("minus_two").as_enum(MessageKind::MessageKind),
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .drop_column(Messages::Value)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Messages {
+    Table,
+    MessageKind,
+    Value,
+}
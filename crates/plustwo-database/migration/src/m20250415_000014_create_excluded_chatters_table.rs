@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20250313_000001_create_broadcasters_table::Broadcasters,
+    m20250408_000010_create_table_versions_table::track_table_version,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250415_000014_create_excluded_chatters_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ExcludedChatters::Table)
+                    .col(
+                        ColumnDef::new(ExcludedChatters::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ExcludedChatters::BroadcasterId).big_integer())
+                    .col(
+                        ColumnDef::new(ExcludedChatters::ChatterId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ExcludedChatters::Reason).string())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-excluded-chatters-broadcaster-id")
+                            .from(ExcludedChatters::Table, ExcludedChatters::BroadcasterId)
+                            .to(Broadcasters::Table, Broadcasters::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        track_table_version(manager, "excluded_chatters").await?;
+
+        // The most common chat bots are excluded everywhere by default.
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(ExcludedChatters::Table)
+                    .columns([ExcludedChatters::ChatterId, ExcludedChatters::Reason])
+                    .values_panic([19_264_788.into(), "Nightbot".into()])
+                    .values_panic([100_135_110.into(), "StreamElements".into()])
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ExcludedChatters::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ExcludedChatters {
+    Table,
+
+    Id,
+    BroadcasterId,
+    ChatterId,
+
+    Reason,
+}
@@ -0,0 +1,91 @@
+use extension::postgres::Type;
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+use crate::m20250313_000004_create_message_kind_type::MessageKind;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250422_000015_add_message_kinds"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Message kinds are stored as text outside of Postgres, which takes any kind.
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        for kind in [
+            AddedMessageKind::PlusZero,
+            AddedMessageKind::W,
+            AddedMessageKind::L,
+        ] {
+            manager
+                .alter_type(
+                    Type::alter()
+                        .name(MessageKind::MessageKind)
+                        .add_value(kind)
+                        .if_not_exists()
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres can't remove values from an enum, so the type is rebuilt without them. Ws and
+        // Ls fall back to the vote they stand in for, and neutral votes have no equivalent.
+        let db = manager.get_connection();
+        for table in ["messages", "vote_patterns"] {
+            db.execute_unprepared(&format!(
+                "DELETE FROM {table} WHERE message_kind = 'plus_zero';
+                UPDATE {table} SET message_kind = 'plus_two' WHERE message_kind = 'w';
+                UPDATE {table} SET message_kind = 'minus_two' WHERE message_kind = 'l';"
+            ))
+            .await?;
+        }
+
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        db.execute_unprepared(
+            "ALTER TYPE message_kind RENAME TO message_kind_old;
+            CREATE TYPE message_kind AS ENUM ('plus_two', 'minus_two');
+            ALTER TABLE messages ALTER COLUMN message_kind
+                TYPE message_kind USING message_kind::text::message_kind;
+            ALTER TABLE vote_patterns ALTER COLUMN message_kind
+                TYPE message_kind USING message_kind::text::message_kind;
+            DROP TYPE message_kind_old;",
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+pub enum AddedMessageKind {
+    PlusZero,
+    W,
+    L,
+}
+impl Iden for AddedMessageKind {
+    fn unquoted(&self, s: &mut dyn std::fmt::Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                AddedMessageKind::PlusZero => "plus_zero",
+                AddedMessageKind::W => "w",
+                AddedMessageKind::L => "l",
+            }
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250422_000016_create_query_indexes"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-messages-broadcast-id-sent-at")
+                    .table(Messages::Table)
+                    .col(Messages::BroadcastId)
+                    .col(Messages::SentAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-messages-chatter-id")
+                    .table(Messages::Table)
+                    .col(Messages::ChatterId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-broadcasts-broadcaster-id-started-at")
+                    .table(Broadcasts::Table)
+                    .col(Broadcasts::BroadcasterId)
+                    .col(Broadcasts::StartedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Each broadcaster has at most one open broadcast, so this stays tiny.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE INDEX "idx-broadcasts-open" ON broadcasts (broadcaster_id)
+                WHERE ended_at IS NULL;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for name in [
+            "idx-broadcasts-open",
+            "idx-broadcasts-broadcaster-id-started-at",
+            "idx-messages-chatter-id",
+            "idx-messages-broadcast-id-sent-at",
+        ] {
+            manager
+                .drop_index(Index::drop().name(name).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Messages {
+    Table,
+    BroadcastId,
+    ChatterId,
+    SentAt,
+}
+
+#[derive(Iden)]
+enum Broadcasts {
+    Table,
+    BroadcasterId,
+    StartedAt,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20250313_000002_create_broadcasts_table::Broadcasts;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250429_000017_create_broadcast_minute_stats_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BroadcastMinuteStats::Table)
+                    .col(
+                        ColumnDef::new(BroadcastMinuteStats::BroadcastId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BroadcastMinuteStats::Minute)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BroadcastMinuteStats::PlusCount)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BroadcastMinuteStats::MinusCount)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BroadcastMinuteStats::PlusValue)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BroadcastMinuteStats::MinusValue)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BroadcastMinuteStats::Voters)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(BroadcastMinuteStats::BroadcastId)
+                            .col(BroadcastMinuteStats::Minute),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-broadcast-minute-stats-broadcast-id")
+                            .from(
+                                BroadcastMinuteStats::Table,
+                                BroadcastMinuteStats::BroadcastId,
+                            )
+                            .to(Broadcasts::Table, Broadcasts::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BroadcastMinuteStats::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum BroadcastMinuteStats {
+    Table,
+
+    BroadcastId,
+    Minute,
+    PlusCount,
+    MinusCount,
+    PlusValue,
+    MinusValue,
+    Voters,
+}
@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20250313_000003_create_chatters_table::Chatters;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250429_000018_create_chatter_names_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatterNames::Table)
+                    .col(
+                        ColumnDef::new(ChatterNames::ChatterId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ChatterNames::DisplayName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ChatterNames::FirstSeenAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ChatterNames::LastSeenAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ChatterNames::ChatterId)
+                            .col(ChatterNames::DisplayName),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-chatter-names-chatter-id")
+                            .from(ChatterNames::Table, ChatterNames::ChatterId)
+                            .to(Chatters::Table, Chatters::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every chatter's current name is seen across their messages. Chatters without any
+        // messages are seen at the epoch, so the first real sighting takes over.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r"
+                INSERT INTO chatter_names (chatter_id, display_name, first_seen_at, last_seen_at)
+                SELECT
+                    chatters.id,
+                    chatters.display_name,
+                    COALESCE(MIN(sent.sent_at), '1970-01-01 00:00:00'),
+                    COALESCE(MAX(sent.sent_at), '1970-01-01 00:00:00')
+                FROM chatters
+                LEFT JOIN (
+                    SELECT chatter_id, sent_at FROM messages
+                    UNION ALL
+                    SELECT chatter_id, sent_at FROM message_contents
+                ) sent ON sent.chatter_id = chatters.id
+                GROUP BY chatters.id, chatters.display_name;
+                ",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ChatterNames::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ChatterNames {
+    Table,
+
+    ChatterId,
+    DisplayName,
+    FirstSeenAt,
+    LastSeenAt,
+}
@@ -0,0 +1,55 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250506_000019_add_login_to_broadcasters"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Broadcasters::Table)
+                    .add_column(ColumnDef::new(Broadcasters::Login).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // An ASCII display name only differs from the login by case. Any other login is filled in
+        // by the watcher the next time it refreshes broadcasters.
+        let ascii = match manager.get_database_backend() {
+            DbBackend::Postgres => "display_name ~ '^[A-Za-z0-9_]+$'",
+            _ => "display_name <> '' AND display_name NOT GLOB '*[^A-Za-z0-9_]*'",
+        };
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                "UPDATE broadcasters SET login = lower(display_name) WHERE {ascii};"
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Broadcasters::Table)
+                    .drop_column(Broadcasters::Login)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Broadcasters {
+    Table,
+    Login,
+}
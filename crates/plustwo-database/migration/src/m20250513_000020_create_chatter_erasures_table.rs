@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250513_000020_create_chatter_erasures_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The log never records who was erased, or which placeholder they were given, so it can't
+        // be used to undo an erasure.
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatterErasures::Table)
+                    .col(
+                        ColumnDef::new(ChatterErasures::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ChatterErasures::Action).string().not_null())
+                    .col(
+                        ColumnDef::new(ChatterErasures::Votes)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ChatterErasures::Reason).string())
+                    .col(
+                        ColumnDef::new(ChatterErasures::ErasedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ChatterErasures::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ChatterErasures {
+    Table,
+
+    Id,
+    Action,
+    Votes,
+    Reason,
+    ErasedAt,
+}
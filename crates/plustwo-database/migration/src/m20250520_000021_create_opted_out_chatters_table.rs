@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20250408_000010_create_table_versions_table::track_table_version;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20250520_000021_create_opted_out_chatters_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Opted out chatters are never recorded, so they don't reference the chatters table.
+        manager
+            .create_table(
+                Table::create()
+                    .table(OptedOutChatters::Table)
+                    .col(
+                        ColumnDef::new(OptedOutChatters::ChatterId)
+                            .big_integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(OptedOutChatters::Reason).string())
+                    .col(
+                        ColumnDef::new(OptedOutChatters::OptedOutAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        track_table_version(manager, "opted_out_chatters").await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OptedOutChatters::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum OptedOutChatters {
+    Table,
+
+    ChatterId,
+
+    Reason,
+    OptedOutAt,
+}
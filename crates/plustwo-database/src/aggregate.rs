@@ -0,0 +1,201 @@
+use std::num::NonZeroU32;
+
+use chrono::TimeDelta;
+use sea_orm::{
+    ColumnTrait as _, ConnectionTrait, DbBackend, EntityTrait as _, QueryFilter as _,
+    QueryOrder as _, QuerySelect as _, QueryTrait as _, Select,
+    sea_query::{Expr, Func, Order, Query, SimpleExpr},
+};
+
+use crate::{DatabaseClient, DatabaseError, DateTime, entities};
+
+type Messages = entities::messages::Entity;
+type Column = entities::messages::Column;
+
+/// Vote totals over a set of messages. Suppressed votes aren't counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Score {
+    /// The total value of positive votes.
+    pub plus: i64,
+    /// The total value of negative votes, which is never positive.
+    pub minus: i64,
+    /// The total value of every vote.
+    pub net: i64,
+    /// The number of votes, including neutral ones.
+    pub votes: i64,
+    /// The number of distinct chatters who voted.
+    pub voters: i64,
+}
+impl From<(i64, i64, i64, i64, i64)> for Score {
+    fn from(score: (i64, i64, i64, i64, i64)) -> Self {
+        Self {
+            plus: score.0,
+            minus: score.1,
+            net: score.2,
+            votes: score.3,
+            voters: score.4,
+        }
+    }
+}
+
+/// The votes cast by a single chatter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChatterScore {
+    pub chatter_id: i64,
+    pub score: Score,
+}
+
+/// The votes cast within a single bucket of a time series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreBucket {
+    /// The start of the bucket, which is aligned to a multiple of the interval since the epoch.
+    pub start: DateTime,
+    pub score: Score,
+}
+
+impl<C: ConnectionTrait> DatabaseClient<C> {
+    /// Totals the votes in a broadcast. If `clamp` is set, each vote's value is clamped to
+    /// `-clamp..=clamp` first, so a single huge vote can't dominate the total.
+    pub async fn broadcast_score(
+        &self,
+        broadcast_id: i64,
+        clamp: Option<NonZeroU32>,
+    ) -> Result<Score, DatabaseError> {
+        let score = select_score(Messages::find(), clamp)
+            .filter(Column::BroadcastId.eq(broadcast_id))
+            .into_tuple::<(i64, i64, i64, i64, i64)>()
+            .one(&self.db)
+            .await?;
+
+        Ok(score.map(Score::from).unwrap_or_default())
+    }
+
+    /// Totals the votes sent to a broadcaster within the range, across all of their broadcasts.
+    pub async fn broadcaster_score(
+        &self,
+        broadcaster_id: i64,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+        clamp: Option<NonZeroU32>,
+    ) -> Result<Score, DatabaseError> {
+        let score = select_score(Messages::find(), clamp)
+            .filter(
+                Column::BroadcastId.in_subquery(
+                    Query::select()
+                        .column(entities::broadcasts::Column::Id)
+                        .from(entities::broadcasts::Entity)
+                        .and_where(entities::broadcasts::Column::BroadcasterId.eq(broadcaster_id))
+                        .to_owned(),
+                ),
+            )
+            .apply_if(from, |q, from| q.filter(Column::SentAt.gte(from)))
+            .apply_if(to, |q, to| q.filter(Column::SentAt.lt(to)))
+            .into_tuple::<(i64, i64, i64, i64, i64)>()
+            .one(&self.db)
+            .await?;
+
+        Ok(score.map(Score::from).unwrap_or_default())
+    }
+
+    /// Totals the votes cast by each chatter in a broadcast, highest net score first.
+    pub async fn chatter_scores(
+        &self,
+        broadcast_id: i64,
+        clamp: Option<NonZeroU32>,
+    ) -> Result<Vec<ChatterScore>, DatabaseError> {
+        let scores = select_score(Messages::find(), clamp)
+            .column(Column::ChatterId)
+            .filter(Column::BroadcastId.eq(broadcast_id))
+            .group_by(Column::ChatterId)
+            .order_by(Expr::cust("net"), Order::Desc)
+            .order_by_asc(Column::ChatterId)
+            .into_tuple::<(i64, i64, i64, i64, i64, i64)>()
+            .all(&self.db)
+            .await?;
+
+        Ok(scores
+            .into_iter()
+            .map(
+                |(plus, minus, net, votes, voters, chatter_id)| ChatterScore {
+                    chatter_id,
+                    score: Score::from((plus, minus, net, votes, voters)),
+                },
+            )
+            .collect())
+    }
+
+    /// Totals the votes in a broadcast over time, in buckets of `interval` (rounded down to whole
+    /// seconds). Buckets without any votes are skipped.
+    pub async fn score_series(
+        &self,
+        broadcast_id: i64,
+        interval: TimeDelta,
+        clamp: Option<NonZeroU32>,
+    ) -> Result<Vec<ScoreBucket>, DatabaseError> {
+        let seconds = interval.num_seconds().max(1);
+        let start = Expr::cust(match self.db.get_database_backend() {
+            DbBackend::Sqlite => format!(
+                "datetime(CAST(strftime('%s', sent_at) AS INTEGER) / {seconds} * {seconds}, \
+                'unixepoch')"
+            ),
+            _ => format!(
+                "to_timestamp(floor(extract(epoch from sent_at) / {seconds}) * {seconds}) \
+                AT TIME ZONE 'UTC'"
+            ),
+        });
+
+        let buckets = select_score(Messages::find(), clamp)
+            .column_as(start.clone(), "start")
+            .filter(Column::BroadcastId.eq(broadcast_id))
+            .group_by(start.clone())
+            .order_by(start, Order::Asc)
+            .into_tuple::<(i64, i64, i64, i64, i64, DateTime)>()
+            .all(&self.db)
+            .await?;
+
+        Ok(buckets
+            .into_iter()
+            .map(|(plus, minus, net, votes, voters, start)| ScoreBucket {
+                start,
+                score: Score::from((plus, minus, net, votes, voters)),
+            })
+            .collect())
+    }
+}
+
+/// Selects the columns of a [`Score`] from counted votes, in field order.
+fn select_score(query: Select<Messages>, clamp: Option<NonZeroU32>) -> Select<Messages> {
+    let value = Expr::col(Column::Value);
+    let value = match clamp {
+        Some(clamp) => {
+            // Values are stored as integers, so larger clamps never change them.
+            let clamp = i32::try_from(clamp.get()).unwrap_or(i32::MAX);
+            SimpleExpr::from(Func::greatest([
+                Func::least([value.into(), Expr::value(clamp)]).into(),
+                Expr::value(-clamp),
+            ]))
+        }
+        None => value.into(),
+    };
+    let sum = |expr: SimpleExpr| {
+        SimpleExpr::from(Func::coalesce([Func::sum(expr).into(), Expr::value(0)]))
+    };
+    let when = |positive: bool| {
+        let condition = if positive {
+            Expr::expr(value.clone()).gt(0)
+        } else {
+            Expr::expr(value.clone()).lt(0)
+        };
+
+        sum(Expr::case(condition, value.clone()).finally(0).into())
+    };
+
+    query
+        .select_only()
+        .column_as(when(true), "plus")
+        .column_as(when(false), "minus")
+        .column_as(sum(value.clone()), "net")
+        .column_as(Expr::col(Column::Id).count(), "votes")
+        .column_as(Expr::col(Column::ChatterId).count_distinct(), "voters")
+        .filter(Column::Suppressed.eq(false))
+}
@@ -9,12 +9,20 @@ pub struct Model {
     pub id: i64,
     pub display_name: String,
     pub profile_image_url: Option<String>,
+    pub store_message_text: bool,
+    pub message_text_retention_days: Option<i32>,
+    pub vote_cooldown_seconds: Option<i32>,
+    pub login: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::broadcasts::Entity")]
     Broadcasts,
+    #[sea_orm(has_many = "super::excluded_chatters::Entity")]
+    ExcludedChatters,
+    #[sea_orm(has_many = "super::vote_patterns::Entity")]
+    VotePatterns,
 }
 
 impl Related<super::broadcasts::Entity> for Entity {
@@ -23,4 +31,16 @@ impl Related<super::broadcasts::Entity> for Entity {
     }
 }
 
+impl Related<super::excluded_chatters::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExcludedChatters.def()
+    }
+}
+
+impl Related<super::vote_patterns::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VotePatterns.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
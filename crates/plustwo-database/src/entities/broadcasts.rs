@@ -15,6 +15,8 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::broadcast_minute_stats::Entity")]
+    BroadcastMinuteStats,
     #[sea_orm(
         belongs_to = "super::broadcasters::Entity",
         from = "Column::BroadcasterId",
@@ -23,16 +25,30 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Broadcasters,
+    #[sea_orm(has_many = "super::message_contents::Entity")]
+    MessageContents,
     #[sea_orm(has_many = "super::messages::Entity")]
     Messages,
 }
 
+impl Related<super::broadcast_minute_stats::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BroadcastMinuteStats.def()
+    }
+}
+
 impl Related<super::broadcasters::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Broadcasters.def()
     }
 }
 
+impl Related<super::message_contents::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MessageContents.def()
+    }
+}
+
 impl Related<super::messages::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Messages.def()
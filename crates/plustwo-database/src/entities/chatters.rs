@@ -12,13 +12,21 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
-    #[sea_orm(has_many = "super::messages::Entity")]
-    Messages,
+    #[sea_orm(has_many = "super::chatter_names::Entity")]
+    ChatterNames,
+    #[sea_orm(has_many = "super::message_contents::Entity")]
+    MessageContents,
 }
 
-impl Related<super::messages::Entity> for Entity {
+impl Related<super::chatter_names::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Messages.def()
+        Relation::ChatterNames.def()
+    }
+}
+
+impl Related<super::message_contents::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MessageContents.def()
     }
 }
 
@@ -12,6 +12,9 @@ pub struct Model {
     pub chatter_id: i64,
     pub sent_at: DateTime,
     pub message_kind: MessageKind,
+    pub target_chatter_id: Option<i64>,
+    pub value: i32,
+    pub suppressed: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -31,7 +34,17 @@ pub enum Relation {
         on_update = "NoAction",
         on_delete = "NoAction"
     )]
-    Chatters,
+    Chatters2,
+    #[sea_orm(
+        belongs_to = "super::chatters::Entity",
+        from = "Column::TargetChatterId",
+        to = "super::chatters::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Chatters1,
+    #[sea_orm(has_one = "super::message_contents::Entity")]
+    MessageContents,
 }
 
 impl Related<super::broadcasts::Entity> for Entity {
@@ -40,9 +53,9 @@ impl Related<super::broadcasts::Entity> for Entity {
     }
 }
 
-impl Related<super::chatters::Entity> for Entity {
+impl Related<super::message_contents::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Chatters.def()
+        Relation::MessageContents.def()
     }
 }
 
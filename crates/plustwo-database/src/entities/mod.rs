@@ -2,8 +2,17 @@
 
 pub mod prelude;
 
+pub mod broadcast_minute_stats;
 pub mod broadcasters;
 pub mod broadcasts;
+pub mod chatter_erasures;
+pub mod chatter_names;
 pub mod chatters;
+pub mod excluded_chatters;
+pub mod message_contents;
+pub mod message_roles;
 pub mod messages;
+pub mod opted_out_chatters;
 pub mod sea_orm_active_enums;
+pub mod table_versions;
+pub mod vote_patterns;
@@ -1,6 +1,15 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.7
 
+pub use super::broadcast_minute_stats::Entity as BroadcastMinuteStats;
 pub use super::broadcasters::Entity as Broadcasters;
 pub use super::broadcasts::Entity as Broadcasts;
+pub use super::chatter_erasures::Entity as ChatterErasures;
+pub use super::chatter_names::Entity as ChatterNames;
 pub use super::chatters::Entity as Chatters;
+pub use super::excluded_chatters::Entity as ExcludedChatters;
+pub use super::message_contents::Entity as MessageContents;
+pub use super::message_roles::Entity as MessageRoles;
 pub use super::messages::Entity as Messages;
+pub use super::opted_out_chatters::Entity as OptedOutChatters;
+pub use super::table_versions::Entity as TableVersions;
+pub use super::vote_patterns::Entity as VotePatterns;
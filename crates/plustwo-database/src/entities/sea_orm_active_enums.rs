@@ -9,4 +9,20 @@ pub enum MessageKind {
     PlusTwo,
     #[sea_orm(string_value = "minus_two")]
     MinusTwo,
+    #[sea_orm(string_value = "plus_zero")]
+    PlusZero,
+    #[sea_orm(string_value = "w")]
+    W,
+    #[sea_orm(string_value = "l")]
+    L,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "vote_pattern_kind")]
+pub enum VotePatternKind {
+    #[sea_orm(string_value = "literal")]
+    Literal,
+    #[sea_orm(string_value = "regex")]
+    Regex,
+    #[sea_orm(string_value = "emote")]
+    Emote,
 }
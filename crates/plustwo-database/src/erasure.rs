@@ -0,0 +1,252 @@
+use sea_orm::{
+    ActiveModelTrait as _,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait as _, Condition, ConnectionTrait, EntityTrait as _, QueryFilter as _,
+    QueryOrder as _, QuerySelect as _, TransactionTrait,
+    sea_query::{Expr, Query, SelectStatement},
+};
+
+use crate::{DatabaseClient, DatabaseError, DateTime, entities};
+
+type Messages = entities::messages::Entity;
+type MessagesColumn = entities::messages::Column;
+type Contents = entities::message_contents::Entity;
+type ContentsColumn = entities::message_contents::Column;
+
+/// How a chatter who asked to be forgotten is erased.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Erasure {
+    /// Deletes the chatter along with every vote they sent.
+    Delete,
+    /// Moves the chatter's votes to an anonymous placeholder, so every score stays intact.
+    Anonymize,
+}
+impl Erasure {
+    /// The action recorded in the audit log.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Anonymize => "anonymize",
+        }
+    }
+}
+
+impl<C: ConnectionTrait + TransactionTrait> DatabaseClient<C> {
+    /// Forgets a chatter, recording the erasure in the audit log. Their names, message text and
+    /// roles are always deleted, and votes targeting or replying to them no longer point at them.
+    /// They're also opted out, so they aren't recorded again.
+    ///
+    /// The log never says who was erased, or which placeholder they were given.
+    ///
+    /// Everything is erased in a single transaction, so the chatter is either fully erased or left
+    /// untouched.
+    pub async fn erase_chatter(
+        &self,
+        chatter_id: i64,
+        erasure: Erasure,
+        reason: Option<String>,
+        erased_at: DateTime,
+    ) -> Result<entities::chatter_erasures::Model, DatabaseError> {
+        let tx = DatabaseClient {
+            db: self.db.begin().await?,
+            batch_size: self.batch_size,
+            parallelism: 1,
+        };
+        let logged = tx.erase(chatter_id, erasure, reason, erased_at).await?;
+        tx.commit().await?;
+
+        Ok(logged)
+    }
+
+    /// Retrieves every erasure in the audit log, oldest first.
+    pub async fn select_chatter_erasures(
+        &self,
+    ) -> Result<Vec<entities::chatter_erasures::Model>, DatabaseError> {
+        Ok(entities::chatter_erasures::Entity::find()
+            .order_by_asc(entities::chatter_erasures::Column::Id)
+            .all(&self.db)
+            .await?)
+    }
+}
+
+impl<C: ConnectionTrait> DatabaseClient<C> {
+    async fn erase(
+        &self,
+        chatter_id: i64,
+        erasure: Erasure,
+        reason: Option<String>,
+        erased_at: DateTime,
+    ) -> Result<entities::chatter_erasures::Model, DatabaseError> {
+        if self.get_chatter(chatter_id).await?.is_none() {
+            return Err(DatabaseError::ChatterNotFound { chatter_id });
+        }
+
+        // Roles don't reference chatters, so they're found through the chatter's messages.
+        entities::message_roles::Entity::delete_many()
+            .filter(
+                Condition::any()
+                    .add(entities::message_roles::Column::Id.in_subquery(sent_by(
+                        entities::messages::Entity,
+                        MessagesColumn::Id,
+                        MessagesColumn::ChatterId,
+                        chatter_id,
+                    )))
+                    .add(entities::message_roles::Column::Id.in_subquery(sent_by(
+                        entities::message_contents::Entity,
+                        ContentsColumn::Id,
+                        ContentsColumn::ChatterId,
+                        chatter_id,
+                    ))),
+            )
+            .exec(&self.db)
+            .await?;
+        Contents::delete_many()
+            .filter(ContentsColumn::ChatterId.eq(chatter_id))
+            .exec(&self.db)
+            .await?;
+
+        let votes = match erasure {
+            Erasure::Delete => self.delete_votes(chatter_id).await?,
+            Erasure::Anonymize => self.anonymize_votes(chatter_id, erased_at).await?,
+        };
+
+        entities::chatter_names::Entity::delete_many()
+            .filter(entities::chatter_names::Column::ChatterId.eq(chatter_id))
+            .exec(&self.db)
+            .await?;
+        entities::chatters::Entity::delete_by_id(chatter_id)
+            .exec(&self.db)
+            .await?;
+
+        entities::opted_out_chatters::Entity::insert(entities::opted_out_chatters::ActiveModel {
+            chatter_id: Set(chatter_id),
+            reason: Set(reason.clone()),
+            opted_out_at: Set(erased_at),
+        })
+        .on_conflict_do_nothing()
+        .exec(&self.db)
+        .await?;
+
+        Ok(entities::chatter_erasures::ActiveModel {
+            id: NotSet,
+            action: Set(erasure.as_str().to_string()),
+            votes: Set(i64::try_from(votes).expect("vote count should fit in a bigint")),
+            reason: Set(reason),
+            erased_at: Set(erased_at),
+        }
+        .insert(&self.db)
+        .await?)
+    }
+
+    /// Deletes every vote sent by a chatter, returning how many were deleted. Votes which targeted
+    /// or replied to them are kept without a target.
+    async fn delete_votes(&self, chatter_id: i64) -> Result<u64, DatabaseError> {
+        let broadcasts: Vec<i64> = Messages::find()
+            .select_only()
+            .column(MessagesColumn::BroadcastId)
+            .distinct()
+            .filter(MessagesColumn::ChatterId.eq(chatter_id))
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        let deleted = Messages::delete_many()
+            .filter(MessagesColumn::ChatterId.eq(chatter_id))
+            .exec(&self.db)
+            .await?
+            .rows_affected;
+        Messages::update_many()
+            .col_expr(
+                MessagesColumn::TargetChatterId,
+                Expr::value(Option::<i64>::None),
+            )
+            .filter(MessagesColumn::TargetChatterId.eq(chatter_id))
+            .exec(&self.db)
+            .await?;
+        Contents::update_many()
+            .col_expr(
+                ContentsColumn::ReplyToChatterId,
+                Expr::value(Option::<i64>::None),
+            )
+            .col_expr(
+                ContentsColumn::ReplyToDisplayName,
+                Expr::value(Option::<String>::None),
+            )
+            .filter(ContentsColumn::ReplyToChatterId.eq(chatter_id))
+            .exec(&self.db)
+            .await?;
+
+        for broadcast_id in broadcasts {
+            self.rebuild_minute_stats(broadcast_id).await?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Moves every vote sent by, targeting or replying to a chatter to a new placeholder, returning
+    /// how many votes they sent. Each erased chatter gets their own placeholder, so voter counts don't
+    /// change.
+    async fn anonymize_votes(
+        &self,
+        chatter_id: i64,
+        erased_at: DateTime,
+    ) -> Result<u64, DatabaseError> {
+        // Twitch IDs are positive, so placeholders count down from -1 and never collide with a
+        // real chatter.
+        let placeholder = entities::chatters::Entity::find()
+            .filter(entities::chatters::Column::Id.lt(0))
+            .order_by_asc(entities::chatters::Column::Id)
+            .one(&self.db)
+            .await?
+            .map_or(-1, |placeholder| placeholder.id - 1);
+
+        // Placeholders are never shared, so this fails rather than merging two chatters.
+        let display_name = format!("anonymous{}", -placeholder);
+        entities::chatters::Entity::insert(entities::chatters::ActiveModel {
+            id: Set(placeholder),
+            display_name: Set(display_name.clone()),
+        })
+        .exec(&self.db)
+        .await?;
+        self.insert_chatter(placeholder, display_name.clone(), erased_at)
+            .await?;
+
+        let moved = Messages::update_many()
+            .col_expr(MessagesColumn::ChatterId, Expr::value(placeholder))
+            .filter(MessagesColumn::ChatterId.eq(chatter_id))
+            .exec(&self.db)
+            .await?
+            .rows_affected;
+        Messages::update_many()
+            .col_expr(MessagesColumn::TargetChatterId, Expr::value(placeholder))
+            .filter(MessagesColumn::TargetChatterId.eq(chatter_id))
+            .exec(&self.db)
+            .await?;
+        Contents::update_many()
+            .col_expr(ContentsColumn::ReplyToChatterId, Expr::value(placeholder))
+            .col_expr(
+                ContentsColumn::ReplyToDisplayName,
+                Expr::value(display_name),
+            )
+            .filter(ContentsColumn::ReplyToChatterId.eq(chatter_id))
+            .exec(&self.db)
+            .await?;
+
+        Ok(moved)
+    }
+}
+
+/// Selects the IDs of a chatter's rows in a table of messages.
+fn sent_by(
+    table: impl sea_orm::Iden + 'static,
+    id: impl sea_orm::Iden + 'static,
+    chatter: impl sea_orm::Iden + 'static,
+    chatter_id: i64,
+) -> SelectStatement {
+    Query::select()
+        .column(id)
+        .from(table)
+        .and_where(Expr::col(chatter).eq(chatter_id))
+        .to_owned()
+}
@@ -0,0 +1,81 @@
+use std::{fmt, ops::Range};
+
+use sea_orm::{DbErr, SqlErr};
+
+/// An error returned by the database.
+#[derive(Debug)]
+pub enum DatabaseError {
+    /// There was no broadcast to update. `broadcast_id` is `None` when looking for the
+    /// broadcaster's open broadcast.
+    BroadcastNotFound {
+        broadcaster_id: i64,
+        broadcast_id: Option<i64>,
+    },
+    /// There was no chatter with the ID.
+    ChatterNotFound { chatter_id: i64 },
+    /// A row conflicted with an existing row.
+    Conflict(DbErr),
+    /// The database couldn't be reached.
+    Connection(DbErr),
+    /// A query failed for any other reason.
+    Query(DbErr),
+    /// A batch of rows in a bulk insert couldn't be inserted.
+    Batch {
+        table: String,
+        /// The indices of the failed rows within the insert.
+        rows: Range<usize>,
+        source: Box<Self>,
+    },
+}
+impl From<DbErr> for DatabaseError {
+    fn from(error: DbErr) -> Self {
+        if matches!(error, DbErr::Conn(_) | DbErr::ConnectionAcquire(_)) {
+            Self::Connection(error)
+        } else if matches!(error, DbErr::RecordNotInserted)
+            || matches!(error.sql_err(), Some(SqlErr::UniqueConstraintViolation(_)))
+        {
+            Self::Conflict(error)
+        } else {
+            Self::Query(error)
+        }
+    }
+}
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BroadcastNotFound {
+                broadcaster_id,
+                broadcast_id: Some(broadcast_id),
+            } => write!(
+                f,
+                "broadcast {broadcast_id} from broadcaster {broadcaster_id} doesn't exist"
+            ),
+            Self::BroadcastNotFound {
+                broadcaster_id,
+                broadcast_id: None,
+            } => write!(f, "broadcaster {broadcaster_id} has no open broadcast"),
+            Self::ChatterNotFound { chatter_id } => write!(f, "chatter {chatter_id} doesn't exist"),
+            Self::Conflict(error) => write!(f, "conflicting row: {error}"),
+            Self::Connection(error) => write!(f, "failed to connect: {error}"),
+            Self::Query(error) => write!(f, "query failed: {error}"),
+            Self::Batch {
+                table,
+                rows,
+                source,
+            } => write!(
+                f,
+                "failed to insert rows {}..{} into {table}: {source}",
+                rows.start, rows.end
+            ),
+        }
+    }
+}
+impl std::error::Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::BroadcastNotFound { .. } | Self::ChatterNotFound { .. } => None,
+            Self::Conflict(error) | Self::Connection(error) | Self::Query(error) => Some(error),
+            Self::Batch { source, .. } => Some(source),
+        }
+    }
+}
@@ -1,54 +1,143 @@
 use entities::sea_orm_active_enums::MessageKind;
 use entities::{
     broadcasters::Entity as Broadcasters, broadcasts::Entity as Broadcasts,
-    chatters::Entity as Chatters, messages::Entity as Messages,
+    chatters::Entity as Chatters, excluded_chatters::Entity as ExcludedChatters,
+    message_contents::Entity as MessageContents, message_roles::Entity as MessageRoles,
+    messages::Entity as Messages, opted_out_chatters::Entity as OptedOutChatters,
+    table_versions::Entity as TableVersions, vote_patterns::Entity as VotePatterns,
 };
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityOrSelect, IntoActiveModel, QueryFilter};
+use futures::{StreamExt as _, TryStreamExt as _, stream};
 use sea_orm::{
-    ActiveValue::{Set, Unchanged},
-    Database, DatabaseConnection, EntityTrait as _,
-    sea_query::OnConflict,
+    ActiveEnum as _, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, IntoActiveModel,
+    Iterable as _, QueryFilter, QueryOrder, QueryTrait as _, TransactionTrait as _,
 };
+use sea_orm::{
+    ActiveValue::Set,
+    Database, DatabaseConnection, DbBackend, EntityName as _, EntityTrait,
+    sea_query::{Expr, OnConflict, Query, SimpleExpr},
+};
+
+pub use aggregate::{ChatterScore, Score, ScoreBucket};
+pub use erasure::Erasure;
+pub use error::DatabaseError;
+pub use memory::MemoryStorage;
+pub use sea_orm::prelude::{DateTime, Json, Uuid};
+pub use storage::{Storage, StorageTransaction, Transactional};
 
-pub use sea_orm::prelude::{DateTime, Uuid};
+mod aggregate;
+mod erasure;
+mod error;
+mod memory;
+mod names;
+mod rollup;
+mod storage;
 
+// Entities are generated, so lints which would require changing the schema are ignored.
+#[allow(clippy::struct_excessive_bools)]
 pub mod entities;
 
-pub struct DatabaseClient {
-    db: DatabaseConnection,
+/// Postgres can't bind more parameters than this in a single statement.
+const MAX_BIND_PARAMETERS: usize = 65_535;
+/// The equivalent of [`MAX_BIND_PARAMETERS`] for `SQLite`.
+const SQLITE_MAX_BIND_PARAMETERS: usize = 32_766;
+
+pub struct DatabaseClient<C = DatabaseConnection> {
+    db: C,
+    /// The most rows inserted by a single statement, if lower than the bind parameter limit.
+    batch_size: Option<usize>,
+    /// How many batches are inserted at once.
+    parallelism: usize,
 }
+
+/// A database client whose changes are only applied once committed.
+pub type DatabaseTransaction = DatabaseClient<sea_orm::DatabaseTransaction>;
+
 impl DatabaseClient {
     /// Constructs a new database client and connects to the URL.
-    pub async fn new(url: &str) -> Result<Self, sea_orm::DbErr> {
-        let db = Database::connect(url).await?;
+    pub async fn new(url: &str) -> Result<Self, DatabaseError> {
+        Ok(Database::connect(url).await?.into())
+    }
+
+    /// Limits the number of rows inserted by each statement in bulk inserts. Batches are always
+    /// small enough to fit within the bind parameter limit.
+    #[must_use]
+    pub const fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
 
-        Ok(Self { db })
+    /// Sets how many batches bulk inserts send at once. Transactions use a single connection, so
+    /// their batches are always sent one at a time.
+    #[must_use]
+    pub const fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism;
+        self
     }
 
+    /// Starts a transaction. Dropping the transaction without committing it rolls back every
+    /// change made through it.
+    pub async fn begin(&self) -> Result<DatabaseTransaction, DatabaseError> {
+        Ok(DatabaseClient {
+            db: self.db.begin().await?,
+            batch_size: self.batch_size,
+            parallelism: 1,
+        })
+    }
+}
+impl From<DatabaseConnection> for DatabaseClient {
+    /// Wraps an existing connection, such as one which has just had its migrations applied.
+    fn from(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            batch_size: None,
+            parallelism: 1,
+        }
+    }
+}
+impl DatabaseTransaction {
+    /// Applies every change made through the transaction.
+    pub async fn commit(self) -> Result<(), DatabaseError> {
+        Ok(self.db.commit().await?)
+    }
+}
+impl<C: ConnectionTrait> DatabaseClient<C> {
     /// Retrieves a complete list of broadcasters.
     pub async fn select_broadcasters(
         &self,
-    ) -> Result<Vec<entities::broadcasters::Model>, sea_orm::DbErr> {
-        Broadcasters::find().all(&self.db).await
+    ) -> Result<Vec<entities::broadcasters::Model>, DatabaseError> {
+        Ok(Broadcasters::find().all(&self.db).await?)
+    }
+
+    pub async fn get_broadcaster(
+        &self,
+        broadcaster_id: i64,
+    ) -> Result<Option<entities::broadcasters::Model>, DatabaseError> {
+        Ok(Broadcasters::find_by_id(broadcaster_id)
+            .one(&self.db)
+            .await?)
     }
 
     /// Inserts a new broadcaster into the database, updating if the entry already exists.
     pub async fn insert_broadcaster(
         &self,
         id: i64,
+        login: &str,
         display_name: &str,
         profile_image_url: &str,
-    ) -> Result<(), sea_orm::DbErr> {
+    ) -> Result<(), DatabaseError> {
         let broadcaster = entities::broadcasters::ActiveModel {
             id: Set(id),
+            login: Set(Some(login.to_string())),
             display_name: Set(display_name.to_string()),
             profile_image_url: Set(Some(profile_image_url.to_string())),
+            ..Default::default()
         };
 
         Broadcasters::insert(broadcaster)
             .on_conflict(
                 OnConflict::column(entities::broadcasters::Column::Id)
                     .update_columns([
+                        entities::broadcasters::Column::Login,
                         entities::broadcasters::Column::DisplayName,
                         entities::broadcasters::Column::ProfileImageUrl,
                     ])
@@ -60,34 +149,78 @@ impl DatabaseClient {
         Ok(())
     }
 
-    /// Inserts a new chatter, updating display name if the chatter already exists.
+    pub async fn get_chatter(
+        &self,
+        chatter_id: i64,
+    ) -> Result<Option<entities::chatters::Model>, DatabaseError> {
+        Ok(Chatters::find_by_id(chatter_id).one(&self.db).await?)
+    }
+
+    /// Records a chatter seen with a display name, like [`Self::insert_many_chatters`].
     pub async fn insert_chatter(
         &self,
         id: i64,
         display_name: String,
-    ) -> Result<(), sea_orm::DbErr> {
-        let chatter = entities::chatters::ActiveModel {
-            id: Set(id),
-            display_name: Set(display_name),
-        };
+        seen_at: DateTime,
+    ) -> Result<(), DatabaseError> {
+        self.insert_many_chatters(&[(entities::chatters::Model { id, display_name }, seen_at)])
+            .await
+    }
 
-        Chatters::insert(chatter)
-            .on_conflict(
-                OnConflict::column(entities::chatters::Column::Id)
-                    .update_column(entities::chatters::Column::DisplayName)
-                    .to_owned(),
-            )
-            .exec(&self.db)
-            .await?;
+    /// Records chatters seen with a display name at a point in time, inserting any new chatters.
+    ///
+    /// Every name is kept in the chatter's name history. Their current name is whichever they were
+    /// seen with most recently, no matter what order sightings are recorded in.
+    pub async fn insert_many_chatters(
+        &self,
+        sightings: &[(entities::chatters::Model, DateTime)],
+    ) -> Result<(), DatabaseError> {
+        let names = names::merge(sightings);
+        let mut ids: Vec<_> = names.iter().map(|name| name.chatter_id).collect();
+        ids.dedup();
+
+        // New chatters start with any of their names, which is corrected once the history has
+        // been updated.
+        let mut chatters = names
+            .iter()
+            .map(|name| entities::chatters::Model {
+                id: name.chatter_id,
+                display_name: name.display_name.clone(),
+            })
+            .collect::<Vec<_>>();
+        chatters.dedup_by_key(|chatter| chatter.id);
+
+        self.insert_in_batches(
+            chatters
+                .into_iter()
+                .map(IntoActiveModel::into_active_model)
+                .collect(),
+        )
+        .await?;
+        self.upsert_in_batches(
+            names
+                .into_iter()
+                .map(IntoActiveModel::into_active_model)
+                .collect(),
+            names::widen_on_conflict(),
+        )
+        .await?;
+
+        let backend = self.db.get_database_backend();
+        for ids in ids.chunks(self.max_bind_parameters()) {
+            let update =
+                names::update_current(entities::chatters::Column::Id.is_in(ids.iter().copied()));
+            self.db.execute(backend.build(&update)).await?;
+        }
 
         Ok(())
     }
 
-    pub async fn insert_many_chatters(
+    pub async fn insert_message(
         &self,
-        chatters: impl Iterator<Item = &entities::chatters::Model>,
-    ) -> Result<(), sea_orm::DbErr> {
-        Chatters::insert_many(chatters.cloned().map(IntoActiveModel::into_active_model))
+        message: entities::messages::Model,
+    ) -> Result<(), DatabaseError> {
+        Messages::insert(message.into_active_model())
             .on_conflict_do_nothing()
             .exec(&self.db)
             .await?;
@@ -95,23 +228,76 @@ impl DatabaseClient {
         Ok(())
     }
 
-    pub async fn insert_message(
+    pub async fn insert_many_messages(
+        &self,
+        messages: &[entities::messages::Model],
+    ) -> Result<(), DatabaseError> {
+        self.insert_in_batches(
+            messages
+                .iter()
+                .cloned()
+                .map(IntoActiveModel::into_active_model)
+                .collect(),
+        )
+        .await
+    }
+
+    /// Retrieves every vote in a broadcast.
+    pub async fn select_messages(
         &self,
-        id: Uuid,
         broadcast_id: i64,
-        chatter_id: i64,
-        sent_at: DateTime,
+    ) -> Result<Vec<entities::messages::Model>, DatabaseError> {
+        Ok(Messages::find()
+            .filter(entities::messages::Column::BroadcastId.eq(broadcast_id))
+            .filter(known_message_kind(entities::messages::Column::MessageKind))
+            .all(&self.db)
+            .await?)
+    }
+
+    pub async fn update_message_vote(
+        &self,
+        id: Uuid,
         message_kind: MessageKind,
-    ) -> Result<(), sea_orm::DbErr> {
-        let message = entities::messages::ActiveModel {
-            id: Set(id),
-            broadcast_id: Set(broadcast_id),
-            chatter_id: Set(chatter_id),
-            sent_at: Set(sent_at),
-            message_kind: Set(message_kind),
-        };
+        target_chatter_id: Option<i64>,
+        value: i32,
+        suppressed: bool,
+    ) -> Result<(), DatabaseError> {
+        Messages::update_many()
+            .col_expr(
+                entities::messages::Column::MessageKind,
+                message_kind.as_enum(),
+            )
+            .col_expr(
+                entities::messages::Column::TargetChatterId,
+                Expr::value(target_chatter_id),
+            )
+            .col_expr(entities::messages::Column::Value, Expr::value(value))
+            .col_expr(
+                entities::messages::Column::Suppressed,
+                Expr::value(suppressed),
+            )
+            .filter(entities::messages::Column::Id.eq(id))
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
 
-        Messages::insert(message)
+    pub async fn delete_many_messages(&self, ids: &[Uuid]) -> Result<(), DatabaseError> {
+        Messages::delete_many()
+            .filter(entities::messages::Column::Id.is_in(ids.iter().copied()))
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stores the raw text of a message, for broadcasters who have opted in.
+    pub async fn insert_message_content(
+        &self,
+        content: entities::message_contents::Model,
+    ) -> Result<(), DatabaseError> {
+        MessageContents::insert(content.into_active_model())
             .on_conflict_do_nothing()
             .exec(&self.db)
             .await?;
@@ -119,22 +305,105 @@ impl DatabaseClient {
         Ok(())
     }
 
-    pub async fn insert_many_messages(
+    pub async fn insert_many_message_contents(
         &self,
-        messages: &[entities::messages::Model],
-    ) -> Result<(), sea_orm::DbErr> {
-        Messages::insert_many(
-            messages
+        contents: &[entities::message_contents::Model],
+    ) -> Result<(), DatabaseError> {
+        self.insert_in_batches(
+            contents
                 .iter()
                 .cloned()
-                .map(IntoActiveModel::into_active_model),
+                .map(IntoActiveModel::into_active_model)
+                .collect(),
         )
-        .on_conflict_do_nothing()
-        .exec(&self.db)
-        .await?;
+        .await
+    }
+
+    /// Stores the roles a chatter had when they sent a message.
+    pub async fn insert_message_roles(
+        &self,
+        roles: entities::message_roles::Model,
+    ) -> Result<(), DatabaseError> {
+        MessageRoles::insert(roles.into_active_model())
+            .on_conflict_do_nothing()
+            .exec(&self.db)
+            .await?;
+
         Ok(())
     }
 
+    pub async fn insert_many_message_roles(
+        &self,
+        roles: &[entities::message_roles::Model],
+    ) -> Result<(), DatabaseError> {
+        self.insert_in_batches(
+            roles
+                .iter()
+                .cloned()
+                .map(IntoActiveModel::into_active_model)
+                .collect(),
+        )
+        .await
+    }
+
+    /// Retrieves the stored text of every vote in a broadcast sent within the range.
+    pub async fn select_message_contents(
+        &self,
+        broadcast_id: i64,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> Result<Vec<entities::message_contents::Model>, DatabaseError> {
+        Ok(MessageContents::find()
+            .filter(entities::message_contents::Column::BroadcastId.eq(broadcast_id))
+            .apply_if(from, |q, from| {
+                q.filter(entities::message_contents::Column::SentAt.gte(from))
+            })
+            .apply_if(to, |q, to| {
+                q.filter(entities::message_contents::Column::SentAt.lt(to))
+            })
+            .order_by_asc(entities::message_contents::Column::SentAt)
+            .all(&self.db)
+            .await?)
+    }
+
+    /// Deletes stored message text which is older than its broadcaster's retention period,
+    /// returning the number of deleted rows.
+    pub async fn prune_message_contents(&self, now: DateTime) -> Result<u64, DatabaseError> {
+        let broadcasters = Broadcasters::find()
+            .filter(entities::broadcasters::Column::MessageTextRetentionDays.is_not_null())
+            .all(&self.db)
+            .await?;
+
+        let mut deleted = 0;
+        for broadcaster in broadcasters {
+            let Some(days) = broadcaster.message_text_retention_days else {
+                continue;
+            };
+
+            deleted += MessageContents::delete_many()
+                .filter(
+                    entities::message_contents::Column::SentAt
+                        .lt(now - chrono::Duration::days(days.into())),
+                )
+                .filter(
+                    entities::message_contents::Column::BroadcastId.in_subquery(
+                        Query::select()
+                            .column(entities::broadcasts::Column::Id)
+                            .from(Broadcasts)
+                            .and_where(
+                                entities::broadcasts::Column::BroadcasterId.eq(broadcaster.id),
+                            )
+                            .to_owned(),
+                    ),
+                )
+                .exec(&self.db)
+                .await?
+                .rows_affected;
+        }
+
+        Ok(deleted)
+    }
+
     /// Inserts a new broadcast attributed to the broadcaster
     pub async fn start_broadcast(
         &self,
@@ -142,7 +411,7 @@ impl DatabaseClient {
         broadcaster_id: i64,
         title: String,
         started_at: DateTime,
-    ) -> Result<(), sea_orm::DbErr> {
+    ) -> Result<(), DatabaseError> {
         let broadcast = entities::broadcasts::ActiveModel {
             id: Set(broadcast_id),
             broadcaster_id: Set(broadcaster_id),
@@ -169,7 +438,7 @@ impl DatabaseClient {
         broadcaster_id: i64,
         ended_at: DateTime,
         broadcast_id: Option<i64>,
-    ) -> Result<(), sea_orm::DbErr> {
+    ) -> Result<(), DatabaseError> {
         // If a broadcast ID is provided, we can use it to find the broadcast.
         // Otherwise, we need to find the most recent broadcast that has not ended yet.
 
@@ -183,8 +452,12 @@ impl DatabaseClient {
                 .await?
         };
 
-        // TODO: Better error handling
-        let mut broadcast = broadcast.unwrap().into_active_model();
+        let mut broadcast = broadcast
+            .ok_or(DatabaseError::BroadcastNotFound {
+                broadcaster_id,
+                broadcast_id,
+            })?
+            .into_active_model();
         broadcast.ended_at = Set(Some(ended_at));
 
         broadcast.update(&self.db).await?;
@@ -192,10 +465,156 @@ impl DatabaseClient {
         Ok(())
     }
 
+    /// Retrieves every broadcast which overlaps the range, optionally limited to a single
+    /// broadcaster.
+    pub async fn select_broadcasts(
+        &self,
+        broadcaster_id: Option<i64>,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> Result<Vec<entities::broadcasts::Model>, DatabaseError> {
+        Ok(Broadcasts::find()
+            .apply_if(broadcaster_id, |q, id| {
+                q.filter(entities::broadcasts::Column::BroadcasterId.eq(id))
+            })
+            .apply_if(from, |q, from| {
+                q.filter(
+                    Condition::any()
+                        .add(entities::broadcasts::Column::EndedAt.is_null())
+                        .add(entities::broadcasts::Column::EndedAt.gte(from)),
+                )
+            })
+            .apply_if(to, |q, to| {
+                q.filter(entities::broadcasts::Column::StartedAt.lt(to))
+            })
+            .order_by_asc(entities::broadcasts::Column::StartedAt)
+            .all(&self.db)
+            .await?)
+    }
+
     pub async fn get_broadcast(
         &self,
         broadcast_id: i64,
-    ) -> Result<Option<entities::broadcasts::Model>, sea_orm::DbErr> {
-        Broadcasts::find_by_id(broadcast_id).one(&self.db).await
+    ) -> Result<Option<entities::broadcasts::Model>, DatabaseError> {
+        Ok(Broadcasts::find_by_id(broadcast_id).one(&self.db).await?)
+    }
+
+    /// Retrieves every vote pattern, both global and per-broadcaster.
+    pub async fn select_vote_patterns(
+        &self,
+    ) -> Result<Vec<entities::vote_patterns::Model>, DatabaseError> {
+        Ok(VotePatterns::find()
+            .filter(known_message_kind(
+                entities::vote_patterns::Column::MessageKind,
+            ))
+            .order_by_asc(entities::vote_patterns::Column::Id)
+            .all(&self.db)
+            .await?)
+    }
+
+    /// Retrieves every chatter whose votes are ignored, both globally and per-broadcaster.
+    pub async fn select_excluded_chatters(
+        &self,
+    ) -> Result<Vec<entities::excluded_chatters::Model>, DatabaseError> {
+        Ok(ExcludedChatters::find().all(&self.db).await?)
+    }
+
+    /// Retrieves every chatter who has asked never to be recorded.
+    pub async fn select_opted_out_chatters(
+        &self,
+    ) -> Result<Vec<entities::opted_out_chatters::Model>, DatabaseError> {
+        Ok(OptedOutChatters::find().all(&self.db).await?)
+    }
+
+    /// Inserts rows in batches small enough to fit within the bind parameter limit, skipping
+    /// any which conflict with existing rows.
+    async fn insert_in_batches<A>(&self, models: Vec<A>) -> Result<(), DatabaseError>
+    where
+        A: ActiveModelTrait + Send + Sync,
+        <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    {
+        let primary_key = <A::Entity as EntityTrait>::PrimaryKey::iter();
+
+        self.upsert_in_batches(
+            models,
+            OnConflict::columns(primary_key).do_nothing().to_owned(),
+        )
+        .await
     }
+
+    /// Inserts rows in batches small enough to fit within the bind parameter limit, resolving
+    /// conflicts with existing rows through `on_conflict`.
+    async fn upsert_in_batches<A>(
+        &self,
+        models: Vec<A>,
+        on_conflict: OnConflict,
+    ) -> Result<(), DatabaseError>
+    where
+        A: ActiveModelTrait + Send + Sync,
+        <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    {
+        let table = A::Entity::default().table_name().to_string();
+        let columns = <A::Entity as EntityTrait>::Column::iter().count();
+        let batch_size = self
+            .batch_size
+            .unwrap_or(usize::MAX)
+            .min(self.max_bind_parameters() / columns)
+            .max(1);
+
+        // Statements are built up front so the stream only owns its items.
+        let inserts = models
+            .chunks(batch_size)
+            .enumerate()
+            .map(|(i, batch)| {
+                let insert = <A::Entity as EntityTrait>::insert_many(batch.iter().cloned())
+                    .on_conflict(on_conflict.clone());
+
+                (insert, i * batch_size..i * batch_size + batch.len())
+            })
+            .collect::<Vec<_>>();
+
+        stream::iter(inserts)
+            .map(|(insert, rows)| {
+                let table = table.clone();
+
+                async move {
+                    insert
+                        .exec_without_returning(&self.db)
+                        .await
+                        .map_err(|source| DatabaseError::Batch {
+                            table,
+                            rows,
+                            source: Box::new(source.into()),
+                        })
+                }
+            })
+            .buffer_unordered(self.parallelism.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(())
+    }
+
+    /// The most parameters which can be bound in a single statement.
+    fn max_bind_parameters(&self) -> usize {
+        match self.db.get_database_backend() {
+            DbBackend::Sqlite => SQLITE_MAX_BIND_PARAMETERS,
+            _ => MAX_BIND_PARAMETERS,
+        }
+    }
+
+    /// Retrieves the current version of a table, which is bumped every time the table changes.
+    /// Tables which have never changed are at version 0.
+    pub async fn get_table_version(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        Ok(TableVersions::find_by_id(table_name)
+            .one(&self.db)
+            .await?
+            .map_or(0, |v| v.version))
+    }
+}
+
+/// Skips rows with a message kind added to the database after this build, which would otherwise
+/// fail to deserialize and take the whole query down with them.
+fn known_message_kind(column: impl ColumnTrait) -> SimpleExpr {
+    column.is_in(MessageKind::iter())
 }
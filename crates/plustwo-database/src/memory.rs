@@ -0,0 +1,662 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use chrono::{DurationRound as _, TimeDelta};
+
+use crate::{
+    DatabaseError, DateTime, Uuid,
+    entities::{self, sea_orm_active_enums::MessageKind},
+    names,
+    storage::{Storage, StorageTransaction, Transactional},
+};
+
+/// A change made through a transaction, which is replayed once the transaction is committed.
+type Change = Arc<dyn Fn(&mut Tables) + Send + Sync>;
+
+/// Storage kept in memory, for testing code written against [`Storage`] without a database.
+///
+/// Conflicting rows are resolved like they are by the database, but foreign keys aren't checked.
+/// Transactions are themselves [`MemoryStorage`], working on a copy of the tables until their
+/// changes are replayed onto the storage they were started from.
+#[derive(Default)]
+pub struct MemoryStorage {
+    inner: Arc<Inner>,
+}
+#[derive(Default)]
+struct Inner {
+    tables: Mutex<Tables>,
+    /// The storage a transaction was started from, and the changes to replay onto it.
+    parent: Option<(MemoryStorage, Mutex<Vec<Change>>)>,
+}
+impl MemoryStorage {
+    /// Constructs empty storage, without the rows seeded by migrations.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a broadcaster with every setting, replacing any existing broadcaster.
+    pub fn set_broadcaster(&self, broadcaster: entities::broadcasters::Model) {
+        self.apply(move |tables| {
+            tables
+                .broadcasters
+                .insert(broadcaster.id, broadcaster.clone());
+            Ok(())
+        })
+        .expect("replacing a broadcaster can't fail");
+    }
+
+    /// Replaces every vote pattern, bumping the table's version.
+    pub fn set_vote_patterns(&self, patterns: Vec<entities::vote_patterns::Model>) {
+        self.apply(move |tables| {
+            tables.vote_patterns.clone_from(&patterns);
+            tables.vote_patterns.sort_by_key(|pattern| pattern.id);
+            tables.bump_version("vote_patterns");
+            Ok(())
+        })
+        .expect("replacing vote patterns can't fail");
+    }
+
+    /// Replaces every excluded chatter, bumping the table's version.
+    pub fn set_excluded_chatters(&self, exclusions: Vec<entities::excluded_chatters::Model>) {
+        self.apply(move |tables| {
+            tables.excluded_chatters.clone_from(&exclusions);
+            tables.bump_version("excluded_chatters");
+            Ok(())
+        })
+        .expect("replacing excluded chatters can't fail");
+    }
+
+    /// Replaces every opted out chatter, bumping the table's version.
+    pub fn set_opted_out_chatters(&self, opt_outs: Vec<entities::opted_out_chatters::Model>) {
+        self.apply(move |tables| {
+            tables.opted_out_chatters.clone_from(&opt_outs);
+            tables.bump_version("opted_out_chatters");
+            Ok(())
+        })
+        .expect("replacing opted out chatters can't fail");
+    }
+
+    fn read<R>(&self, read: impl FnOnce(&Tables) -> R) -> R {
+        read(&lock(&self.inner.tables))
+    }
+
+    /// Makes a change, recording it to be replayed if this is a transaction. Failed changes
+    /// aren't recorded, since they leave the tables untouched.
+    fn apply<T>(
+        &self,
+        change: impl Fn(&mut Tables) -> Result<T, DatabaseError> + Send + Sync + 'static,
+    ) -> Result<T, DatabaseError> {
+        let result = change(&mut lock(&self.inner.tables))?;
+        if let Some((_, changes)) = &self.inner.parent {
+            lock(changes).push(Arc::new(move |tables| {
+                let _ = change(tables);
+            }));
+        }
+
+        Ok(result)
+    }
+
+    /// Applies a committed transaction's changes all at once.
+    fn replay(&self, changes: Vec<Change>) {
+        let mut tables = lock(&self.inner.tables);
+        for change in &changes {
+            change(&mut tables);
+        }
+        if let Some((_, pending)) = &self.inner.parent {
+            lock(pending).extend(changes);
+        }
+    }
+
+    fn transaction(&self) -> Self {
+        let parent = Self {
+            inner: Arc::clone(&self.inner),
+        };
+
+        Self {
+            inner: Arc::new(Inner {
+                tables: Mutex::new(self.read(Tables::clone)),
+                parent: Some((parent, Mutex::default())),
+            }),
+        }
+    }
+
+    fn commit_changes(&self) {
+        if let Some((parent, changes)) = &self.inner.parent {
+            parent.replay(std::mem::take(&mut lock(changes)));
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Clone, Default)]
+struct Tables {
+    broadcasters: BTreeMap<i64, entities::broadcasters::Model>,
+    broadcasts: BTreeMap<i64, entities::broadcasts::Model>,
+    chatters: BTreeMap<i64, entities::chatters::Model>,
+    chatter_names: BTreeMap<(i64, String), entities::chatter_names::Model>,
+    messages: BTreeMap<Uuid, entities::messages::Model>,
+    message_contents: BTreeMap<Uuid, entities::message_contents::Model>,
+    message_roles: BTreeMap<Uuid, entities::message_roles::Model>,
+    minute_stats: BTreeMap<(i64, DateTime), entities::broadcast_minute_stats::Model>,
+    vote_patterns: Vec<entities::vote_patterns::Model>,
+    excluded_chatters: Vec<entities::excluded_chatters::Model>,
+    opted_out_chatters: Vec<entities::opted_out_chatters::Model>,
+    table_versions: HashMap<String, i64>,
+}
+impl Tables {
+    fn bump_version(&mut self, table_name: &str) {
+        *self
+            .table_versions
+            .entry(table_name.to_string())
+            .or_default() += 1;
+    }
+
+    /// Records chatters like [`crate::DatabaseClient::insert_many_chatters`], returning the number
+    /// of new chatters.
+    fn insert_chatters(&mut self, sightings: &[(entities::chatters::Model, DateTime)]) -> u64 {
+        let mut inserted = 0;
+        let mut ids = BTreeSet::new();
+        for name in names::merge(sightings) {
+            ids.insert(name.chatter_id);
+            self.chatters.entry(name.chatter_id).or_insert_with(|| {
+                inserted += 1;
+                entities::chatters::Model {
+                    id: name.chatter_id,
+                    display_name: name.display_name.clone(),
+                }
+            });
+            self.chatter_names
+                .entry((name.chatter_id, name.display_name.clone()))
+                .and_modify(|existing| {
+                    existing.first_seen_at = existing.first_seen_at.min(name.first_seen_at);
+                    existing.last_seen_at = existing.last_seen_at.max(name.last_seen_at);
+                })
+                .or_insert(name);
+        }
+
+        // The current name is the one seen most recently, with ties going to the first name.
+        for id in ids {
+            let current = self
+                .chatter_names
+                .range((id, String::new())..)
+                .map(|(_, name)| name)
+                .take_while(|name| name.chatter_id == id)
+                .max_by(|a, b| {
+                    a.last_seen_at
+                        .cmp(&b.last_seen_at)
+                        .then_with(|| b.display_name.cmp(&a.display_name))
+                })
+                .map(|name| name.display_name.clone());
+            if let (Some(chatter), Some(current)) = (self.chatters.get_mut(&id), current) {
+                chatter.display_name = current;
+            }
+        }
+
+        inserted
+    }
+
+    fn start_broadcast(
+        &mut self,
+        broadcast_id: i64,
+        broadcaster_id: i64,
+        title: &str,
+        started_at: DateTime,
+    ) {
+        self.broadcasts
+            .entry(broadcast_id)
+            .and_modify(|broadcast| broadcast.started_at = started_at)
+            .or_insert_with(|| entities::broadcasts::Model {
+                id: broadcast_id,
+                broadcaster_id,
+                title: title.to_string(),
+                started_at,
+                ended_at: None,
+            });
+    }
+
+    /// Counts every minute of a broadcast's votes, like the database's rollups.
+    fn count_minutes(
+        &self,
+        broadcast_id: i64,
+    ) -> BTreeMap<DateTime, entities::broadcast_minute_stats::Model> {
+        let mut minutes = BTreeMap::new();
+        let mut voters = BTreeSet::new();
+        for message in self.messages.values() {
+            if message.broadcast_id != broadcast_id || message.suppressed {
+                continue;
+            }
+
+            let minute = truncate_to_minute(message.sent_at);
+            let stats = minutes
+                .entry(minute)
+                .or_insert(entities::broadcast_minute_stats::Model {
+                    broadcast_id,
+                    minute,
+                    plus_count: 0,
+                    minus_count: 0,
+                    plus_value: 0,
+                    minus_value: 0,
+                    voters: 0,
+                });
+
+            let value = i64::from(message.value);
+            if value > 0 {
+                stats.plus_count += 1;
+                stats.plus_value += value;
+            } else if value < 0 {
+                stats.minus_count += 1;
+                stats.minus_value += value;
+            }
+            if voters.insert((minute, message.chatter_id)) {
+                stats.voters += 1;
+            }
+        }
+
+        minutes
+    }
+
+    fn rebuild_minute_stats(&mut self, broadcast_id: i64) {
+        let minutes = self.count_minutes(broadcast_id);
+
+        self.minute_stats
+            .retain(|&(id, minute), _| id != broadcast_id || minutes.contains_key(&minute));
+        for (minute, stats) in minutes {
+            self.minute_stats.insert((broadcast_id, minute), stats);
+        }
+    }
+}
+
+/// Inserts rows which don't conflict with an existing row, returning the number inserted.
+fn insert_new<K: Ord, M: Clone>(
+    table: &mut BTreeMap<K, M>,
+    rows: &[M],
+    key: impl Fn(&M) -> K,
+) -> u64 {
+    let mut inserted = 0;
+    for row in rows {
+        table.entry(key(row)).or_insert_with(|| {
+            inserted += 1;
+            row.clone()
+        });
+    }
+
+    inserted
+}
+
+fn truncate_to_minute(time: DateTime) -> DateTime {
+    time.duration_trunc(TimeDelta::minutes(1)).unwrap_or(time)
+}
+
+impl Storage for MemoryStorage {
+    async fn select_broadcasters(
+        &self,
+    ) -> Result<Vec<entities::broadcasters::Model>, DatabaseError> {
+        Ok(self.read(|tables| tables.broadcasters.values().cloned().collect()))
+    }
+
+    async fn get_broadcaster(
+        &self,
+        broadcaster_id: i64,
+    ) -> Result<Option<entities::broadcasters::Model>, DatabaseError> {
+        Ok(self.read(|tables| tables.broadcasters.get(&broadcaster_id).cloned()))
+    }
+
+    async fn insert_broadcaster(
+        &self,
+        id: i64,
+        login: &str,
+        display_name: &str,
+        profile_image_url: &str,
+    ) -> Result<(), DatabaseError> {
+        let broadcaster = entities::broadcasters::Model {
+            id,
+            display_name: display_name.to_string(),
+            profile_image_url: Some(profile_image_url.to_string()),
+            store_message_text: false,
+            message_text_retention_days: None,
+            vote_cooldown_seconds: None,
+            login: Some(login.to_string()),
+        };
+
+        self.apply(move |tables| {
+            tables
+                .broadcasters
+                .entry(id)
+                .and_modify(|existing| {
+                    existing.login.clone_from(&broadcaster.login);
+                    existing.display_name.clone_from(&broadcaster.display_name);
+                    existing
+                        .profile_image_url
+                        .clone_from(&broadcaster.profile_image_url);
+                })
+                .or_insert_with(|| broadcaster.clone());
+            Ok(())
+        })
+    }
+
+    async fn get_chatter(
+        &self,
+        chatter_id: i64,
+    ) -> Result<Option<entities::chatters::Model>, DatabaseError> {
+        Ok(self.read(|tables| tables.chatters.get(&chatter_id).cloned()))
+    }
+
+    async fn insert_chatter(
+        &self,
+        id: i64,
+        display_name: String,
+        seen_at: DateTime,
+    ) -> Result<(), DatabaseError> {
+        self.insert_many_chatters(&[(entities::chatters::Model { id, display_name }, seen_at)])
+            .await
+    }
+
+    async fn insert_many_chatters(
+        &self,
+        sightings: &[(entities::chatters::Model, DateTime)],
+    ) -> Result<(), DatabaseError> {
+        let sightings = sightings.to_vec();
+        self.apply(move |tables| {
+            tables.insert_chatters(&sightings);
+            Ok(())
+        })
+    }
+
+    async fn insert_message(
+        &self,
+        message: entities::messages::Model,
+    ) -> Result<(), DatabaseError> {
+        self.insert_many_messages(&[message]).await
+    }
+
+    async fn insert_many_messages(
+        &self,
+        messages: &[entities::messages::Model],
+    ) -> Result<(), DatabaseError> {
+        let messages = messages.to_vec();
+        self.apply(move |tables| {
+            insert_new(&mut tables.messages, &messages, |message| message.id);
+            Ok(())
+        })
+    }
+
+    async fn select_messages(
+        &self,
+        broadcast_id: i64,
+    ) -> Result<Vec<entities::messages::Model>, DatabaseError> {
+        Ok(self.read(|tables| {
+            tables
+                .messages
+                .values()
+                .filter(|message| message.broadcast_id == broadcast_id)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn update_message_vote(
+        &self,
+        id: Uuid,
+        message_kind: MessageKind,
+        target_chatter_id: Option<i64>,
+        value: i32,
+        suppressed: bool,
+    ) -> Result<(), DatabaseError> {
+        self.apply(move |tables| {
+            if let Some(message) = tables.messages.get_mut(&id) {
+                message.message_kind = message_kind.clone();
+                message.target_chatter_id = target_chatter_id;
+                message.value = value;
+                message.suppressed = suppressed;
+            }
+            Ok(())
+        })
+    }
+
+    async fn delete_many_messages(&self, ids: &[Uuid]) -> Result<(), DatabaseError> {
+        let ids = ids.to_vec();
+        self.apply(move |tables| {
+            // Contents are removed along with their vote, like the foreign key does.
+            for id in &ids {
+                tables.messages.remove(id);
+                tables.message_contents.remove(id);
+            }
+            Ok(())
+        })
+    }
+
+    async fn insert_message_content(
+        &self,
+        content: entities::message_contents::Model,
+    ) -> Result<(), DatabaseError> {
+        self.insert_many_message_contents(&[content]).await
+    }
+
+    async fn insert_many_message_contents(
+        &self,
+        contents: &[entities::message_contents::Model],
+    ) -> Result<(), DatabaseError> {
+        let contents = contents.to_vec();
+        self.apply(move |tables| {
+            insert_new(&mut tables.message_contents, &contents, |content| {
+                content.id
+            });
+            Ok(())
+        })
+    }
+
+    async fn select_message_contents(
+        &self,
+        broadcast_id: i64,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> Result<Vec<entities::message_contents::Model>, DatabaseError> {
+        let mut contents: Vec<_> = self.read(|tables| {
+            tables
+                .message_contents
+                .values()
+                .filter(|content| content.broadcast_id == broadcast_id)
+                .filter(|content| from.is_none_or(|from| content.sent_at >= from))
+                .filter(|content| to.is_none_or(|to| content.sent_at < to))
+                .cloned()
+                .collect()
+        });
+        contents.sort_by_key(|content| content.sent_at);
+
+        Ok(contents)
+    }
+
+    async fn prune_message_contents(&self, now: DateTime) -> Result<u64, DatabaseError> {
+        self.apply(move |tables| {
+            let cutoffs: HashMap<_, _> = tables
+                .broadcasts
+                .values()
+                .filter_map(|broadcast| {
+                    let days = tables
+                        .broadcasters
+                        .get(&broadcast.broadcaster_id)?
+                        .message_text_retention_days?;
+
+                    Some((broadcast.id, now - chrono::Duration::days(days.into())))
+                })
+                .collect();
+
+            let before = tables.message_contents.len();
+            tables.message_contents.retain(|_, content| {
+                cutoffs
+                    .get(&content.broadcast_id)
+                    .is_none_or(|cutoff| content.sent_at >= *cutoff)
+            });
+
+            Ok((before - tables.message_contents.len()) as u64)
+        })
+    }
+
+    async fn insert_message_roles(
+        &self,
+        roles: entities::message_roles::Model,
+    ) -> Result<(), DatabaseError> {
+        self.insert_many_message_roles(&[roles]).await
+    }
+
+    async fn insert_many_message_roles(
+        &self,
+        roles: &[entities::message_roles::Model],
+    ) -> Result<(), DatabaseError> {
+        let roles = roles.to_vec();
+        self.apply(move |tables| {
+            insert_new(&mut tables.message_roles, &roles, |roles| roles.id);
+            Ok(())
+        })
+    }
+
+    async fn start_broadcast(
+        &self,
+        broadcast_id: i64,
+        broadcaster_id: i64,
+        title: String,
+        started_at: DateTime,
+    ) -> Result<(), DatabaseError> {
+        self.apply(move |tables| {
+            tables.start_broadcast(broadcast_id, broadcaster_id, &title, started_at);
+            Ok(())
+        })
+    }
+
+    async fn end_broadcast(
+        &self,
+        broadcaster_id: i64,
+        ended_at: DateTime,
+        broadcast_id: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        self.apply(move |tables| {
+            let broadcast = if let Some(broadcast_id) = broadcast_id {
+                tables.broadcasts.get_mut(&broadcast_id)
+            } else {
+                tables.broadcasts.values_mut().find(|broadcast| {
+                    broadcast.ended_at.is_none() && broadcast.broadcaster_id == broadcaster_id
+                })
+            };
+
+            broadcast
+                .ok_or(DatabaseError::BroadcastNotFound {
+                    broadcaster_id,
+                    broadcast_id,
+                })?
+                .ended_at = Some(ended_at);
+            Ok(())
+        })
+    }
+
+    async fn select_broadcasts(
+        &self,
+        broadcaster_id: Option<i64>,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> Result<Vec<entities::broadcasts::Model>, DatabaseError> {
+        let mut broadcasts: Vec<_> = self.read(|tables| {
+            tables
+                .broadcasts
+                .values()
+                .filter(|broadcast| broadcaster_id.is_none_or(|id| broadcast.broadcaster_id == id))
+                .filter(|broadcast| {
+                    from.is_none_or(|from| broadcast.ended_at.is_none_or(|ended| ended >= from))
+                })
+                .filter(|broadcast| to.is_none_or(|to| broadcast.started_at < to))
+                .cloned()
+                .collect()
+        });
+        broadcasts.sort_by_key(|broadcast| broadcast.started_at);
+
+        Ok(broadcasts)
+    }
+
+    async fn get_broadcast(
+        &self,
+        broadcast_id: i64,
+    ) -> Result<Option<entities::broadcasts::Model>, DatabaseError> {
+        Ok(self.read(|tables| tables.broadcasts.get(&broadcast_id).cloned()))
+    }
+
+    async fn select_minute_stats(
+        &self,
+        broadcast_id: i64,
+    ) -> Result<Vec<entities::broadcast_minute_stats::Model>, DatabaseError> {
+        Ok(self.read(|tables| {
+            tables
+                .minute_stats
+                .values()
+                .filter(|stats| stats.broadcast_id == broadcast_id)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn refresh_minute_stats(
+        &self,
+        broadcast_id: i64,
+        sent_at: DateTime,
+    ) -> Result<(), DatabaseError> {
+        let minute = truncate_to_minute(sent_at);
+        self.apply(move |tables| {
+            if let Some(stats) = tables.count_minutes(broadcast_id).remove(&minute) {
+                tables.minute_stats.insert((broadcast_id, minute), stats);
+            }
+            Ok(())
+        })
+    }
+
+    async fn rebuild_minute_stats(&self, broadcast_id: i64) -> Result<(), DatabaseError> {
+        self.apply(move |tables| {
+            tables.rebuild_minute_stats(broadcast_id);
+            Ok(())
+        })
+    }
+
+    async fn select_vote_patterns(
+        &self,
+    ) -> Result<Vec<entities::vote_patterns::Model>, DatabaseError> {
+        Ok(self.read(|tables| tables.vote_patterns.clone()))
+    }
+
+    async fn select_excluded_chatters(
+        &self,
+    ) -> Result<Vec<entities::excluded_chatters::Model>, DatabaseError> {
+        Ok(self.read(|tables| tables.excluded_chatters.clone()))
+    }
+
+    async fn select_opted_out_chatters(
+        &self,
+    ) -> Result<Vec<entities::opted_out_chatters::Model>, DatabaseError> {
+        Ok(self.read(|tables| tables.opted_out_chatters.clone()))
+    }
+
+    async fn get_table_version(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        Ok(self.read(|tables| {
+            tables
+                .table_versions
+                .get(table_name)
+                .copied()
+                .unwrap_or_default()
+        }))
+    }
+}
+impl Transactional for MemoryStorage {
+    type Transaction = Self;
+
+    async fn begin(&self) -> Result<Self, DatabaseError> {
+        Ok(self.transaction())
+    }
+}
+impl StorageTransaction for MemoryStorage {
+    async fn commit(self) -> Result<(), DatabaseError> {
+        self.commit_changes();
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use sea_orm::{
+    ColumnTrait as _, ConnectionTrait, EntityTrait as _, QueryFilter as _, QueryOrder as _,
+    sea_query::{Alias, Expr, Func, OnConflict, Order, Query, SimpleExpr, UpdateStatement},
+};
+
+use crate::{DatabaseClient, DatabaseError, DateTime, entities};
+
+type ChatterNames = entities::chatter_names::Entity;
+type Column = entities::chatter_names::Column;
+
+impl<C: ConnectionTrait> DatabaseClient<C> {
+    /// Retrieves every display name a chatter has been seen with, oldest first.
+    pub async fn select_chatter_names(
+        &self,
+        chatter_id: i64,
+    ) -> Result<Vec<entities::chatter_names::Model>, DatabaseError> {
+        Ok(ChatterNames::find()
+            .filter(Column::ChatterId.eq(chatter_id))
+            .order_by_asc(Column::FirstSeenAt)
+            .order_by_asc(Column::DisplayName)
+            .all(&self.db)
+            .await?)
+    }
+
+    /// Retrieves the display name a chatter was using at a point in time, which is the last name
+    /// they took up before then. Falls back to their first name if they hadn't been seen yet.
+    pub async fn get_chatter_name_at(
+        &self,
+        chatter_id: i64,
+        at: DateTime,
+    ) -> Result<Option<String>, DatabaseError> {
+        let name = ChatterNames::find()
+            .filter(Column::ChatterId.eq(chatter_id))
+            .filter(Column::FirstSeenAt.lte(at))
+            .order_by_desc(Column::FirstSeenAt)
+            .one(&self.db)
+            .await?;
+        if let Some(name) = name {
+            return Ok(Some(name.display_name));
+        }
+
+        Ok(ChatterNames::find()
+            .filter(Column::ChatterId.eq(chatter_id))
+            .order_by_asc(Column::FirstSeenAt)
+            .one(&self.db)
+            .await?
+            .map(|name| name.display_name))
+    }
+}
+
+/// Merges sightings of the same chatter and name into a single span between when the name was
+/// first and last seen.
+pub fn merge(
+    sightings: &[(entities::chatters::Model, DateTime)],
+) -> Vec<entities::chatter_names::Model> {
+    let mut spans = BTreeMap::<_, (DateTime, DateTime)>::new();
+    for (chatter, seen_at) in sightings {
+        spans
+            .entry((chatter.id, chatter.display_name.as_str()))
+            .and_modify(|(first, last)| {
+                *first = (*first).min(*seen_at);
+                *last = (*last).max(*seen_at);
+            })
+            .or_insert((*seen_at, *seen_at));
+    }
+
+    spans
+        .into_iter()
+        .map(
+            |((chatter_id, display_name), (first_seen_at, last_seen_at))| {
+                entities::chatter_names::Model {
+                    chatter_id,
+                    display_name: display_name.to_string(),
+                    first_seen_at,
+                    last_seen_at,
+                }
+            },
+        )
+        .collect()
+}
+
+/// Widens the span of a name which has been seen before.
+pub fn widen_on_conflict() -> OnConflict {
+    let excluded = |column: Column| Expr::col((Alias::new("excluded"), column));
+
+    OnConflict::columns([Column::ChatterId, Column::DisplayName])
+        .value(
+            Column::FirstSeenAt,
+            Func::least([
+                Expr::col((ChatterNames::default(), Column::FirstSeenAt)).into(),
+                excluded(Column::FirstSeenAt).into(),
+            ]),
+        )
+        .value(
+            Column::LastSeenAt,
+            Func::greatest([
+                Expr::col((ChatterNames::default(), Column::LastSeenAt)).into(),
+                excluded(Column::LastSeenAt).into(),
+            ]),
+        )
+        .to_owned()
+}
+
+/// Sets the current name of the chatters matching `condition` to whichever name they were seen
+/// with most recently.
+pub fn update_current(condition: SimpleExpr) -> UpdateStatement {
+    let latest = Query::select()
+        .column(Column::DisplayName)
+        .from(ChatterNames::default())
+        .and_where(
+            Expr::col((ChatterNames::default(), Column::ChatterId))
+                .equals((entities::chatters::Entity, entities::chatters::Column::Id)),
+        )
+        .order_by(Column::LastSeenAt, Order::Desc)
+        .order_by(Column::DisplayName, Order::Asc)
+        .limit(1)
+        .to_owned();
+
+    Query::update()
+        .table(entities::chatters::Entity)
+        .value(
+            entities::chatters::Column::DisplayName,
+            SimpleExpr::SubQuery(None, Box::new(latest.into_sub_query_statement())),
+        )
+        .and_where(condition)
+        .to_owned()
+}
@@ -0,0 +1,146 @@
+use chrono::{DurationRound as _, TimeDelta};
+use sea_orm::{
+    ColumnTrait as _, ConnectionTrait, DbBackend, EntityTrait as _, QueryFilter as _,
+    QueryOrder as _,
+    sea_query::{
+        DeleteStatement, Expr, Func, InsertStatement, OnConflict, Query, SelectStatement,
+        SimpleExpr,
+    },
+};
+
+use crate::{DatabaseClient, DatabaseError, DateTime, entities};
+
+type Stats = entities::broadcast_minute_stats::Entity;
+type StatsColumn = entities::broadcast_minute_stats::Column;
+type MessagesColumn = entities::messages::Column;
+
+impl<C: ConnectionTrait> DatabaseClient<C> {
+    /// Retrieves the per-minute vote counts of a broadcast, oldest first. Minutes without any
+    /// votes are skipped.
+    pub async fn select_minute_stats(
+        &self,
+        broadcast_id: i64,
+    ) -> Result<Vec<entities::broadcast_minute_stats::Model>, DatabaseError> {
+        Ok(Stats::find()
+            .filter(StatsColumn::BroadcastId.eq(broadcast_id))
+            .order_by_asc(StatsColumn::Minute)
+            .all(&self.db)
+            .await?)
+    }
+
+    /// Recounts the minute of a broadcast containing `sent_at`, after a vote is inserted.
+    ///
+    /// Unique voters can't be counted by incrementing, so the whole minute is recounted, which
+    /// only reads that minute's votes.
+    pub async fn refresh_minute_stats(
+        &self,
+        broadcast_id: i64,
+        sent_at: DateTime,
+    ) -> Result<(), DatabaseError> {
+        let minute = sent_at
+            .duration_trunc(TimeDelta::minutes(1))
+            .unwrap_or(sent_at);
+
+        let backend = self.db.get_database_backend();
+        let mut query = count_minutes(backend, broadcast_id);
+        query
+            .and_where(MessagesColumn::SentAt.gte(minute))
+            .and_where(MessagesColumn::SentAt.lt(minute + TimeDelta::minutes(1)));
+
+        self.db.execute(backend.build(&upsert(query))).await?;
+
+        Ok(())
+    }
+
+    /// Recounts every minute of a broadcast, after its votes are imported or changed in bulk.
+    pub async fn rebuild_minute_stats(&self, broadcast_id: i64) -> Result<(), DatabaseError> {
+        let backend = self.db.get_database_backend();
+        let (upsert, delete) = rebuild(backend, broadcast_id);
+
+        self.db.execute(backend.build(&upsert)).await?;
+        self.db.execute(backend.build(&delete)).await?;
+
+        Ok(())
+    }
+}
+
+/// Builds the statements which recount every minute of a broadcast. Counts are replaced before
+/// stale minutes are deleted, so a broadcast never appears to have no stats while rebuilding.
+pub fn rebuild(backend: DbBackend, broadcast_id: i64) -> (InsertStatement, DeleteStatement) {
+    let delete = Query::delete()
+        .from_table(entities::broadcast_minute_stats::Entity)
+        .and_where(StatsColumn::BroadcastId.eq(broadcast_id))
+        .and_where(
+            Expr::col(StatsColumn::Minute).not_in_subquery(
+                Query::select()
+                    .expr(minute(backend))
+                    .from(entities::messages::Entity)
+                    .and_where(MessagesColumn::BroadcastId.eq(broadcast_id))
+                    .and_where(MessagesColumn::Suppressed.eq(false))
+                    .to_owned(),
+            ),
+        )
+        .to_owned();
+
+    (upsert(count_minutes(backend, broadcast_id)), delete)
+}
+
+fn upsert(counts: SelectStatement) -> InsertStatement {
+    Query::insert()
+        .into_table(entities::broadcast_minute_stats::Entity)
+        .columns([
+            StatsColumn::BroadcastId,
+            StatsColumn::Minute,
+            StatsColumn::PlusCount,
+            StatsColumn::MinusCount,
+            StatsColumn::PlusValue,
+            StatsColumn::MinusValue,
+            StatsColumn::Voters,
+        ])
+        .select_from(counts)
+        .expect("counts should select every stats column")
+        .on_conflict(
+            OnConflict::columns([StatsColumn::BroadcastId, StatsColumn::Minute])
+                .update_columns([
+                    StatsColumn::PlusCount,
+                    StatsColumn::MinusCount,
+                    StatsColumn::PlusValue,
+                    StatsColumn::MinusValue,
+                    StatsColumn::Voters,
+                ])
+                .to_owned(),
+        )
+        .to_owned()
+}
+
+/// Counts a broadcast's votes per minute, in the order of the stats columns. Suppressed votes
+/// aren't counted.
+fn count_minutes(backend: DbBackend, broadcast_id: i64) -> SelectStatement {
+    let value = || Expr::col(MessagesColumn::Value);
+    let sum_when = |condition: SimpleExpr, then: SimpleExpr| {
+        SimpleExpr::from(Func::sum(Expr::case(condition, then).finally(0)))
+    };
+
+    Query::select()
+        .column(MessagesColumn::BroadcastId)
+        .expr(minute(backend))
+        .expr(sum_when(value().gt(0), Expr::value(1)))
+        .expr(sum_when(value().lt(0), Expr::value(1)))
+        .expr(sum_when(value().gt(0), value().into()))
+        .expr(sum_when(value().lt(0), value().into()))
+        .expr(Expr::col(MessagesColumn::ChatterId).count_distinct())
+        .from(entities::messages::Entity)
+        .and_where(MessagesColumn::BroadcastId.eq(broadcast_id))
+        .and_where(MessagesColumn::Suppressed.eq(false))
+        .group_by_col(MessagesColumn::BroadcastId)
+        .add_group_by([minute(backend)])
+        .to_owned()
+}
+
+/// Truncates a vote's send time to the minute.
+fn minute(backend: DbBackend) -> SimpleExpr {
+    match backend {
+        DbBackend::Sqlite => Expr::cust("strftime('%Y-%m-%d %H:%M:00', sent_at)"),
+        _ => Expr::cust("date_trunc('minute', sent_at)"),
+    }
+}
@@ -0,0 +1,397 @@
+use std::future::Future;
+
+use sea_orm::ConnectionTrait;
+
+use crate::{
+    DatabaseClient, DatabaseError, DatabaseTransaction, DateTime, Uuid,
+    entities::{self, sea_orm_active_enums::MessageKind},
+};
+
+/// The storage operations used by the apps, so they can run against [`crate::MemoryStorage`] as
+/// well as a database. Each operation behaves like the [`DatabaseClient`] method of the same
+/// name.
+pub trait Storage: Sync {
+    fn select_broadcasters(
+        &self,
+    ) -> impl Future<Output = Result<Vec<entities::broadcasters::Model>, DatabaseError>> + Send;
+
+    fn get_broadcaster(
+        &self,
+        broadcaster_id: i64,
+    ) -> impl Future<Output = Result<Option<entities::broadcasters::Model>, DatabaseError>> + Send;
+
+    fn insert_broadcaster(
+        &self,
+        id: i64,
+        login: &str,
+        display_name: &str,
+        profile_image_url: &str,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn get_chatter(
+        &self,
+        chatter_id: i64,
+    ) -> impl Future<Output = Result<Option<entities::chatters::Model>, DatabaseError>> + Send;
+
+    fn insert_chatter(
+        &self,
+        id: i64,
+        display_name: String,
+        seen_at: DateTime,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn insert_many_chatters(
+        &self,
+        sightings: &[(entities::chatters::Model, DateTime)],
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn insert_message(
+        &self,
+        message: entities::messages::Model,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn insert_many_messages(
+        &self,
+        messages: &[entities::messages::Model],
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn select_messages(
+        &self,
+        broadcast_id: i64,
+    ) -> impl Future<Output = Result<Vec<entities::messages::Model>, DatabaseError>> + Send;
+
+    fn update_message_vote(
+        &self,
+        id: Uuid,
+        message_kind: MessageKind,
+        target_chatter_id: Option<i64>,
+        value: i32,
+        suppressed: bool,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn delete_many_messages(
+        &self,
+        ids: &[Uuid],
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn insert_message_content(
+        &self,
+        content: entities::message_contents::Model,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn insert_many_message_contents(
+        &self,
+        contents: &[entities::message_contents::Model],
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn select_message_contents(
+        &self,
+        broadcast_id: i64,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> impl Future<Output = Result<Vec<entities::message_contents::Model>, DatabaseError>> + Send;
+
+    fn prune_message_contents(
+        &self,
+        now: DateTime,
+    ) -> impl Future<Output = Result<u64, DatabaseError>> + Send;
+
+    fn insert_message_roles(
+        &self,
+        roles: entities::message_roles::Model,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn insert_many_message_roles(
+        &self,
+        roles: &[entities::message_roles::Model],
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn start_broadcast(
+        &self,
+        broadcast_id: i64,
+        broadcaster_id: i64,
+        title: String,
+        started_at: DateTime,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn end_broadcast(
+        &self,
+        broadcaster_id: i64,
+        ended_at: DateTime,
+        broadcast_id: Option<i64>,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn select_broadcasts(
+        &self,
+        broadcaster_id: Option<i64>,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> impl Future<Output = Result<Vec<entities::broadcasts::Model>, DatabaseError>> + Send;
+
+    fn get_broadcast(
+        &self,
+        broadcast_id: i64,
+    ) -> impl Future<Output = Result<Option<entities::broadcasts::Model>, DatabaseError>> + Send;
+
+    fn select_minute_stats(
+        &self,
+        broadcast_id: i64,
+    ) -> impl Future<Output = Result<Vec<entities::broadcast_minute_stats::Model>, DatabaseError>> + Send;
+
+    fn refresh_minute_stats(
+        &self,
+        broadcast_id: i64,
+        sent_at: DateTime,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn rebuild_minute_stats(
+        &self,
+        broadcast_id: i64,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn select_vote_patterns(
+        &self,
+    ) -> impl Future<Output = Result<Vec<entities::vote_patterns::Model>, DatabaseError>> + Send;
+
+    fn select_excluded_chatters(
+        &self,
+    ) -> impl Future<Output = Result<Vec<entities::excluded_chatters::Model>, DatabaseError>> + Send;
+
+    fn select_opted_out_chatters(
+        &self,
+    ) -> impl Future<Output = Result<Vec<entities::opted_out_chatters::Model>, DatabaseError>> + Send;
+
+    fn get_table_version(
+        &self,
+        table_name: &str,
+    ) -> impl Future<Output = Result<i64, DatabaseError>> + Send;
+}
+
+/// Storage which can group changes together, so that they're applied all at once or not at all.
+pub trait Transactional: Storage {
+    type Transaction: StorageTransaction;
+
+    /// Starts a transaction. Dropping the transaction without committing it rolls back every
+    /// change made through it.
+    fn begin(&self) -> impl Future<Output = Result<Self::Transaction, DatabaseError>> + Send;
+}
+
+/// Changes which are only applied once committed.
+pub trait StorageTransaction: Storage + Send {
+    /// Applies every change made through the transaction.
+    fn commit(self) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+}
+
+impl<C: ConnectionTrait> Storage for DatabaseClient<C> {
+    async fn select_broadcasters(
+        &self,
+    ) -> Result<Vec<entities::broadcasters::Model>, DatabaseError> {
+        Self::select_broadcasters(self).await
+    }
+
+    async fn get_broadcaster(
+        &self,
+        broadcaster_id: i64,
+    ) -> Result<Option<entities::broadcasters::Model>, DatabaseError> {
+        Self::get_broadcaster(self, broadcaster_id).await
+    }
+
+    async fn insert_broadcaster(
+        &self,
+        id: i64,
+        login: &str,
+        display_name: &str,
+        profile_image_url: &str,
+    ) -> Result<(), DatabaseError> {
+        Self::insert_broadcaster(self, id, login, display_name, profile_image_url).await
+    }
+
+    async fn get_chatter(
+        &self,
+        chatter_id: i64,
+    ) -> Result<Option<entities::chatters::Model>, DatabaseError> {
+        Self::get_chatter(self, chatter_id).await
+    }
+
+    async fn insert_chatter(
+        &self,
+        id: i64,
+        display_name: String,
+        seen_at: DateTime,
+    ) -> Result<(), DatabaseError> {
+        Self::insert_chatter(self, id, display_name, seen_at).await
+    }
+
+    async fn insert_many_chatters(
+        &self,
+        sightings: &[(entities::chatters::Model, DateTime)],
+    ) -> Result<(), DatabaseError> {
+        Self::insert_many_chatters(self, sightings).await
+    }
+
+    async fn insert_message(
+        &self,
+        message: entities::messages::Model,
+    ) -> Result<(), DatabaseError> {
+        Self::insert_message(self, message).await
+    }
+
+    async fn insert_many_messages(
+        &self,
+        messages: &[entities::messages::Model],
+    ) -> Result<(), DatabaseError> {
+        Self::insert_many_messages(self, messages).await
+    }
+
+    async fn select_messages(
+        &self,
+        broadcast_id: i64,
+    ) -> Result<Vec<entities::messages::Model>, DatabaseError> {
+        Self::select_messages(self, broadcast_id).await
+    }
+
+    async fn update_message_vote(
+        &self,
+        id: Uuid,
+        message_kind: MessageKind,
+        target_chatter_id: Option<i64>,
+        value: i32,
+        suppressed: bool,
+    ) -> Result<(), DatabaseError> {
+        Self::update_message_vote(self, id, message_kind, target_chatter_id, value, suppressed)
+            .await
+    }
+
+    async fn delete_many_messages(&self, ids: &[Uuid]) -> Result<(), DatabaseError> {
+        Self::delete_many_messages(self, ids).await
+    }
+
+    async fn insert_message_content(
+        &self,
+        content: entities::message_contents::Model,
+    ) -> Result<(), DatabaseError> {
+        Self::insert_message_content(self, content).await
+    }
+
+    async fn insert_many_message_contents(
+        &self,
+        contents: &[entities::message_contents::Model],
+    ) -> Result<(), DatabaseError> {
+        Self::insert_many_message_contents(self, contents).await
+    }
+
+    async fn select_message_contents(
+        &self,
+        broadcast_id: i64,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> Result<Vec<entities::message_contents::Model>, DatabaseError> {
+        Self::select_message_contents(self, broadcast_id, from, to).await
+    }
+
+    async fn prune_message_contents(&self, now: DateTime) -> Result<u64, DatabaseError> {
+        Self::prune_message_contents(self, now).await
+    }
+
+    async fn insert_message_roles(
+        &self,
+        roles: entities::message_roles::Model,
+    ) -> Result<(), DatabaseError> {
+        Self::insert_message_roles(self, roles).await
+    }
+
+    async fn insert_many_message_roles(
+        &self,
+        roles: &[entities::message_roles::Model],
+    ) -> Result<(), DatabaseError> {
+        Self::insert_many_message_roles(self, roles).await
+    }
+
+    async fn start_broadcast(
+        &self,
+        broadcast_id: i64,
+        broadcaster_id: i64,
+        title: String,
+        started_at: DateTime,
+    ) -> Result<(), DatabaseError> {
+        Self::start_broadcast(self, broadcast_id, broadcaster_id, title, started_at).await
+    }
+
+    async fn end_broadcast(
+        &self,
+        broadcaster_id: i64,
+        ended_at: DateTime,
+        broadcast_id: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        Self::end_broadcast(self, broadcaster_id, ended_at, broadcast_id).await
+    }
+
+    async fn select_broadcasts(
+        &self,
+        broadcaster_id: Option<i64>,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> Result<Vec<entities::broadcasts::Model>, DatabaseError> {
+        Self::select_broadcasts(self, broadcaster_id, from, to).await
+    }
+
+    async fn get_broadcast(
+        &self,
+        broadcast_id: i64,
+    ) -> Result<Option<entities::broadcasts::Model>, DatabaseError> {
+        Self::get_broadcast(self, broadcast_id).await
+    }
+
+    async fn select_minute_stats(
+        &self,
+        broadcast_id: i64,
+    ) -> Result<Vec<entities::broadcast_minute_stats::Model>, DatabaseError> {
+        Self::select_minute_stats(self, broadcast_id).await
+    }
+
+    async fn refresh_minute_stats(
+        &self,
+        broadcast_id: i64,
+        sent_at: DateTime,
+    ) -> Result<(), DatabaseError> {
+        Self::refresh_minute_stats(self, broadcast_id, sent_at).await
+    }
+
+    async fn rebuild_minute_stats(&self, broadcast_id: i64) -> Result<(), DatabaseError> {
+        Self::rebuild_minute_stats(self, broadcast_id).await
+    }
+
+    async fn select_vote_patterns(
+        &self,
+    ) -> Result<Vec<entities::vote_patterns::Model>, DatabaseError> {
+        Self::select_vote_patterns(self).await
+    }
+
+    async fn select_excluded_chatters(
+        &self,
+    ) -> Result<Vec<entities::excluded_chatters::Model>, DatabaseError> {
+        Self::select_excluded_chatters(self).await
+    }
+
+    async fn select_opted_out_chatters(
+        &self,
+    ) -> Result<Vec<entities::opted_out_chatters::Model>, DatabaseError> {
+        Self::select_opted_out_chatters(self).await
+    }
+
+    async fn get_table_version(&self, table_name: &str) -> Result<i64, DatabaseError> {
+        Self::get_table_version(self, table_name).await
+    }
+}
+impl Transactional for DatabaseClient {
+    type Transaction = DatabaseTransaction;
+
+    async fn begin(&self) -> Result<DatabaseTransaction, DatabaseError> {
+        Self::begin(self).await
+    }
+}
+impl StorageTransaction for DatabaseTransaction {
+    async fn commit(self) -> Result<(), DatabaseError> {
+        Self::commit(self).await
+    }
+}
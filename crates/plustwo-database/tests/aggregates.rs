@@ -0,0 +1,220 @@
+//! Checks score aggregation against a real schema.
+
+mod common;
+
+use std::num::NonZeroU32;
+
+use chrono::TimeDelta;
+use common::on;
+use plustwo_database::{
+    ChatterScore, DatabaseTransaction, DateTime, Score, ScoreBucket, Uuid,
+    entities::{chatters, messages, sea_orm_active_enums::MessageKind},
+};
+
+const BROADCASTER: i64 = 9_100_000_000_001;
+const FIRST_BROADCAST: i64 = 9_100_000_000_101;
+const SECOND_BROADCAST: i64 = 9_100_000_000_102;
+const CHATTERS: [i64; 3] = [9_100_000_000_201, 9_100_000_000_202, 9_100_000_000_203];
+const CLAMP: NonZeroU32 = NonZeroU32::new(5).unwrap();
+
+const fn vote(
+    id: u128,
+    broadcast_id: i64,
+    chatter_id: i64,
+    sent_at: DateTime,
+    value: i32,
+) -> messages::Model {
+    messages::Model {
+        id: Uuid::from_u128(0x9100_0000_0000_0000 + id),
+        broadcast_id,
+        chatter_id,
+        sent_at,
+        message_kind: match value.signum() {
+            1 => MessageKind::PlusTwo,
+            -1 => MessageKind::MinusTwo,
+            _ => MessageKind::PlusZero,
+        },
+        target_chatter_id: None,
+        value,
+        suppressed: false,
+    }
+}
+
+/// Inserts a broadcaster with two broadcasts, returning the transaction they're visible in.
+async fn fixtures() -> Option<DatabaseTransaction> {
+    let tx = common::begin().await?;
+
+    tx.insert_broadcaster(BROADCASTER, "aggregates", "aggregates", "")
+        .await
+        .expect("broadcaster should insert");
+    tx.start_broadcast(FIRST_BROADCAST, BROADCASTER, "first".into(), on(1, 0, 0))
+        .await
+        .expect("broadcast should start");
+    tx.start_broadcast(SECOND_BROADCAST, BROADCASTER, "second".into(), on(2, 0, 0))
+        .await
+        .expect("broadcast should start");
+
+    let chatters = CHATTERS.map(|id| {
+        let chatter = chatters::Model {
+            id,
+            display_name: format!("chatter{id}"),
+        };
+
+        (chatter, on(1, 0, 0))
+    });
+    tx.insert_many_chatters(&chatters)
+        .await
+        .expect("chatters should insert");
+
+    let [first, second, third] = CHATTERS;
+    let suppressed = messages::Model {
+        suppressed: true,
+        ..vote(5, FIRST_BROADCAST, second, on(1, 2, 0), -5)
+    };
+    tx.insert_many_messages(&[
+        vote(1, FIRST_BROADCAST, first, on(1, 0, 10), 2),
+        vote(2, FIRST_BROADCAST, first, on(1, 0, 50), 10),
+        vote(3, FIRST_BROADCAST, second, on(1, 1, 5), -2),
+        vote(4, FIRST_BROADCAST, third, on(1, 1, 30), 0),
+        suppressed,
+        vote(6, SECOND_BROADCAST, first, on(2, 0, 0), -2),
+    ])
+    .await
+    .expect("messages should insert");
+
+    Some(tx)
+}
+
+const fn score(plus: i64, minus: i64, votes: i64, chatters: i64) -> Score {
+    Score {
+        plus,
+        minus,
+        net: plus + minus,
+        votes,
+        voters: chatters,
+    }
+}
+
+#[tokio::test]
+async fn broadcast_scores() {
+    let Some(tx) = fixtures().await else {
+        return;
+    };
+
+    assert_eq!(
+        tx.broadcast_score(FIRST_BROADCAST, None).await.unwrap(),
+        score(12, -2, 4, 3)
+    );
+    assert_eq!(
+        tx.broadcast_score(FIRST_BROADCAST, Some(CLAMP))
+            .await
+            .unwrap(),
+        score(7, -2, 4, 3)
+    );
+    assert_eq!(
+        tx.broadcast_score(SECOND_BROADCAST, None).await.unwrap(),
+        score(0, -2, 1, 1)
+    );
+    assert_eq!(
+        tx.broadcast_score(9_100_000_000_199, None).await.unwrap(),
+        Score::default()
+    );
+}
+
+#[tokio::test]
+async fn broadcaster_scores() {
+    let Some(tx) = fixtures().await else {
+        return;
+    };
+
+    assert_eq!(
+        tx.broadcaster_score(BROADCASTER, None, None, None)
+            .await
+            .unwrap(),
+        score(12, -4, 5, 3)
+    );
+    assert_eq!(
+        tx.broadcaster_score(BROADCASTER, Some(on(2, 0, 0)), None, None)
+            .await
+            .unwrap(),
+        score(0, -2, 1, 1)
+    );
+    assert_eq!(
+        tx.broadcaster_score(BROADCASTER, Some(on(1, 0, 30)), Some(on(1, 1, 30)), None)
+            .await
+            .unwrap(),
+        score(10, -2, 2, 2)
+    );
+}
+
+#[tokio::test]
+async fn chatter_scores() {
+    let Some(tx) = fixtures().await else {
+        return;
+    };
+
+    let [first, second, third] = CHATTERS;
+    assert_eq!(
+        tx.chatter_scores(FIRST_BROADCAST, None).await.unwrap(),
+        [
+            ChatterScore {
+                chatter_id: first,
+                score: score(12, 0, 2, 1),
+            },
+            ChatterScore {
+                chatter_id: third,
+                score: score(0, 0, 1, 1),
+            },
+            ChatterScore {
+                chatter_id: second,
+                score: score(0, -2, 1, 1),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn score_series() {
+    let Some(tx) = fixtures().await else {
+        return;
+    };
+
+    assert_eq!(
+        tx.score_series(FIRST_BROADCAST, TimeDelta::minutes(1), None)
+            .await
+            .unwrap(),
+        [
+            ScoreBucket {
+                start: on(1, 0, 0),
+                score: score(12, 0, 2, 1),
+            },
+            ScoreBucket {
+                start: on(1, 1, 0),
+                score: score(0, -2, 2, 2),
+            },
+        ]
+    );
+    assert_eq!(
+        tx.score_series(FIRST_BROADCAST, TimeDelta::seconds(30), Some(CLAMP))
+            .await
+            .unwrap(),
+        [
+            ScoreBucket {
+                start: on(1, 0, 0),
+                score: score(2, 0, 1, 1),
+            },
+            ScoreBucket {
+                start: on(1, 0, 30),
+                score: score(5, 0, 1, 1),
+            },
+            ScoreBucket {
+                start: on(1, 1, 0),
+                score: score(0, -2, 1, 1),
+            },
+            ScoreBucket {
+                start: on(1, 1, 30),
+                score: score(0, 0, 1, 1),
+            },
+        ]
+    );
+}
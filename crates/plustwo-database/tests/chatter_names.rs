@@ -0,0 +1,107 @@
+//! Checks that chatter name history is kept however sightings are recorded.
+
+mod common;
+
+use common::{begin, day};
+use plustwo_database::{
+    DatabaseTransaction, DateTime,
+    entities::{chatter_names, chatters},
+};
+
+const CHATTER: i64 = 9_300_000_000_001;
+
+fn seen(display_name: &str, at: u32) -> (chatters::Model, DateTime) {
+    let chatter = chatters::Model {
+        id: CHATTER,
+        display_name: display_name.to_string(),
+    };
+
+    (chatter, day(at))
+}
+
+fn span(display_name: &str, first: u32, last: u32) -> chatter_names::Model {
+    chatter_names::Model {
+        chatter_id: CHATTER,
+        display_name: display_name.to_string(),
+        first_seen_at: day(first),
+        last_seen_at: day(last),
+    }
+}
+
+async fn current_name(tx: &DatabaseTransaction) -> String {
+    tx.get_chatter(CHATTER)
+        .await
+        .unwrap()
+        .expect("chatter should exist")
+        .display_name
+}
+
+#[tokio::test]
+async fn renames_are_kept() {
+    let Some(tx) = begin().await else {
+        return;
+    };
+
+    // Seen live, then renamed.
+    tx.insert_chatter(CHATTER, "first".into(), day(10))
+        .await
+        .unwrap();
+    tx.insert_chatter(CHATTER, "second".into(), day(12))
+        .await
+        .unwrap();
+
+    // An older VOD is archived afterwards, which mustn't undo the rename.
+    tx.insert_many_chatters(&[seen("first", 8), seen("zeroth", 2), seen("first", 5)])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        tx.select_chatter_names(CHATTER).await.unwrap(),
+        [
+            span("zeroth", 2, 2),
+            span("first", 5, 10),
+            span("second", 12, 12)
+        ]
+    );
+    assert_eq!(current_name(&tx).await, "second");
+
+    assert_eq!(
+        tx.get_chatter_name_at(CHATTER, day(1)).await.unwrap(),
+        Some("zeroth".into())
+    );
+    assert_eq!(
+        tx.get_chatter_name_at(CHATTER, day(6)).await.unwrap(),
+        Some("first".into())
+    );
+    assert_eq!(
+        tx.get_chatter_name_at(CHATTER, day(20)).await.unwrap(),
+        Some("second".into())
+    );
+    assert_eq!(
+        tx.get_chatter_name_at(CHATTER + 1, day(20)).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn both_paths_agree_on_the_current_name() {
+    let Some(tx) = begin().await else {
+        return;
+    };
+
+    // A batch containing a rename takes the latest name, whatever order it's in.
+    tx.insert_many_chatters(&[seen("renamed", 4), seen("original", 3)])
+        .await
+        .unwrap();
+    assert_eq!(current_name(&tx).await, "renamed");
+
+    // A single sighting of an older name doesn't revert it.
+    tx.insert_chatter(CHATTER, "original".into(), day(1))
+        .await
+        .unwrap();
+    assert_eq!(current_name(&tx).await, "renamed");
+    assert_eq!(
+        tx.select_chatter_names(CHATTER).await.unwrap(),
+        [span("original", 1, 3), span("renamed", 4, 4)]
+    );
+}
@@ -0,0 +1,71 @@
+//! Connections and timestamps shared by the database tests.
+//!
+//! Postgres tests run against `DATABASE_URL` with every migration applied, and are skipped if it
+//! isn't set. Their fixtures are inserted in a transaction which is rolled back at the end.
+//! `SQLite` tests migrate their own in-memory database, so no database server is needed.
+
+// Each test binary only uses some of the helpers.
+#![allow(dead_code)]
+
+use chrono::NaiveDate;
+use migration::{Migrator, MigratorTrait as _};
+use plustwo_database::{DatabaseClient, DatabaseTransaction, DateTime};
+use sea_orm::{Database, DatabaseConnection};
+
+/// A time on the `day`th of April 2025, when every fixture happens.
+pub fn on(day: u32, minute: u32, second: u32) -> DateTime {
+    NaiveDate::from_ymd_opt(2025, 4, day)
+        .and_then(|date| date.and_hms_opt(0, minute, second))
+        .expect("fixture time should be valid")
+}
+
+/// A time on the first fixture day.
+pub fn at(minute: u32, second: u32) -> DateTime {
+    on(1, minute, second)
+}
+
+/// The start of a fixture day.
+pub fn day(day: u32) -> DateTime {
+    on(day, 0, 0)
+}
+
+/// The Postgres database to test against, if any.
+pub fn database_url() -> Option<String> {
+    let url = std::env::var("DATABASE_URL").ok();
+    if url.is_none() {
+        eprintln!("DATABASE_URL isn't set, skipping");
+    }
+
+    url
+}
+
+pub async fn connect() -> Option<DatabaseClient> {
+    Some(
+        DatabaseClient::new(&database_url()?)
+            .await
+            .expect("database should connect"),
+    )
+}
+
+/// Starts a transaction which is rolled back once it's dropped.
+pub async fn begin() -> Option<DatabaseTransaction> {
+    Some(
+        connect()
+            .await?
+            .begin()
+            .await
+            .expect("transaction should start"),
+    )
+}
+
+/// Opens an in-memory `SQLite` database with every migration applied.
+pub async fn sqlite() -> DatabaseConnection {
+    let db = Database::connect("sqlite::memory:")
+        .await
+        .expect("database should open");
+    Migrator::up(&db, None)
+        .await
+        .expect("migrations should apply");
+
+    db
+}
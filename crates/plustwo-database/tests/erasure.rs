@@ -0,0 +1,229 @@
+//! Checks that erased chatters leave nothing behind but the audit log.
+
+mod common;
+
+use common::at;
+use plustwo_database::{
+    DatabaseClient, DatabaseError, Erasure, Json, Uuid,
+    entities::{
+        message_contents, message_roles, messages, opted_out_chatters,
+        sea_orm_active_enums::MessageKind,
+    },
+};
+
+const BROADCASTER: i64 = 1;
+const BROADCAST: i64 = 101;
+/// The chatter who asks to be forgotten.
+const ERASED: i64 = 201;
+const OTHER: i64 = 202;
+
+fn vote(id: u128, chatter_id: i64, target_chatter_id: Option<i64>) -> messages::Model {
+    messages::Model {
+        id: Uuid::from_u128(id),
+        broadcast_id: BROADCAST,
+        chatter_id,
+        sent_at: at(0, 0),
+        message_kind: MessageKind::PlusTwo,
+        target_chatter_id,
+        value: 2,
+        suppressed: false,
+    }
+}
+
+/// Inserts a broadcast where each chatter replied to the other with a vote, with their text and
+/// roles.
+async fn fixtures() -> DatabaseClient {
+    let db = DatabaseClient::from(common::sqlite().await);
+
+    db.insert_broadcaster(BROADCASTER, "erasure", "Erasure", "")
+        .await
+        .unwrap();
+    db.start_broadcast(BROADCAST, BROADCASTER, "forgotten".into(), at(0, 0))
+        .await
+        .unwrap();
+    db.insert_chatter(ERASED, "erased".into(), at(0, 1))
+        .await
+        .unwrap();
+    db.insert_chatter(OTHER, "other".into(), at(0, 1))
+        .await
+        .unwrap();
+
+    for (id, chatter_id, target) in [(1, ERASED, OTHER), (2, OTHER, ERASED)] {
+        let vote = vote(id, chatter_id, Some(target));
+        db.insert_message(vote.clone()).await.unwrap();
+        db.insert_message_content(message_contents::Model {
+            id: vote.id,
+            broadcast_id: BROADCAST,
+            chatter_id,
+            sent_at: vote.sent_at,
+            text: "+2".into(),
+            fragments: Json::Array(Vec::new()),
+            reply_to_chatter_id: Some(target),
+            reply_to_display_name: Some(format!("chatter{target}")),
+        })
+        .await
+        .unwrap();
+        db.insert_message_roles(message_roles::Model {
+            id: vote.id,
+            is_subscriber: true,
+            is_moderator: false,
+            is_vip: false,
+            is_founder: false,
+            subscriber_months: Some(3),
+        })
+        .await
+        .unwrap();
+        db.refresh_minute_stats(BROADCAST, vote.sent_at)
+            .await
+            .unwrap();
+    }
+
+    db
+}
+
+#[tokio::test]
+async fn deleted_chatters_lose_their_votes() {
+    let db = fixtures().await;
+
+    let logged = db
+        .erase_chatter(ERASED, Erasure::Delete, Some("requested".into()), at(0, 30))
+        .await
+        .unwrap();
+    assert_eq!((logged.votes, logged.action.as_str()), (1, "delete"));
+
+    assert_eq!(db.get_chatter(ERASED).await.unwrap(), None);
+    assert!(db.select_chatter_names(ERASED).await.unwrap().is_empty());
+    assert_eq!(
+        db.select_messages(BROADCAST).await.unwrap(),
+        [vote(2, OTHER, None)]
+    );
+    let contents = db
+        .select_message_contents(BROADCAST, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        contents
+            .iter()
+            .map(|c| (
+                c.chatter_id,
+                c.reply_to_chatter_id,
+                c.reply_to_display_name.as_deref()
+            ))
+            .collect::<Vec<_>>(),
+        [(OTHER, None, None)]
+    );
+    assert_eq!(
+        db.select_minute_stats(BROADCAST).await.unwrap()[0].voters,
+        1
+    );
+    assert_eq!(db.select_chatter_erasures().await.unwrap(), [logged]);
+    assert_eq!(
+        db.select_opted_out_chatters().await.unwrap(),
+        [opted_out_chatters::Model {
+            chatter_id: ERASED,
+            reason: Some("requested".into()),
+            opted_out_at: at(0, 30),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn anonymized_chatters_keep_their_scores() {
+    let db = fixtures().await;
+    let score = db.broadcast_score(BROADCAST, None).await.unwrap();
+
+    let logged = db
+        .erase_chatter(ERASED, Erasure::Anonymize, None, at(0, 30))
+        .await
+        .unwrap();
+    assert_eq!((logged.votes, logged.action.as_str()), (1, "anonymize"));
+
+    let mut votes = db.select_messages(BROADCAST).await.unwrap();
+    votes.sort_by_key(|vote| vote.id);
+    let placeholder = votes[0].chatter_id;
+    assert!(placeholder < 0);
+    assert_eq!(db.get_chatter(ERASED).await.unwrap(), None);
+    assert_eq!(
+        db.get_chatter(placeholder)
+            .await
+            .unwrap()
+            .unwrap()
+            .display_name,
+        format!("anonymous{}", -placeholder)
+    );
+    assert_eq!(
+        votes,
+        [
+            vote(1, placeholder, Some(OTHER)),
+            vote(2, OTHER, Some(placeholder))
+        ]
+    );
+    assert_eq!(db.broadcast_score(BROADCAST, None).await.unwrap(), score);
+    let display_name = format!("anonymous{}", -placeholder);
+    assert_eq!(
+        db.select_message_contents(BROADCAST, None, None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|c| (
+                c.chatter_id,
+                c.reply_to_chatter_id,
+                c.reply_to_display_name.as_deref()
+            ))
+            .collect::<Vec<_>>(),
+        [(OTHER, Some(placeholder), Some(display_name.as_str()))]
+    );
+
+    // Each chatter gets their own placeholder.
+    db.erase_chatter(OTHER, Erasure::Anonymize, None, at(0, 40))
+        .await
+        .unwrap();
+    assert_eq!(
+        db.select_minute_stats(BROADCAST).await.unwrap()[0].voters,
+        2
+    );
+}
+
+#[tokio::test]
+async fn erased_chatters_are_opted_out() {
+    let db = fixtures().await;
+
+    for chatter_id in [ERASED, OTHER] {
+        db.erase_chatter(chatter_id, Erasure::Anonymize, None, at(0, 30))
+            .await
+            .unwrap();
+    }
+
+    let mut votes = db.select_messages(BROADCAST).await.unwrap();
+    votes.sort_by_key(|vote| vote.id);
+    let placeholders = [votes[0].chatter_id, votes[1].chatter_id];
+    assert_ne!(placeholders[0], placeholders[1]);
+    assert_eq!(
+        votes,
+        [
+            vote(1, placeholders[0], Some(placeholders[1])),
+            vote(2, placeholders[1], Some(placeholders[0]))
+        ]
+    );
+    assert_eq!(
+        db.select_opted_out_chatters()
+            .await
+            .unwrap()
+            .iter()
+            .map(|opt_out| opt_out.chatter_id)
+            .collect::<Vec<_>>(),
+        [ERASED, OTHER]
+    );
+}
+
+#[tokio::test]
+async fn missing_chatters_are_reported() {
+    let db = fixtures().await;
+
+    assert!(matches!(
+        db.erase_chatter(OTHER + 1, Erasure::Delete, None, at(0, 30))
+            .await,
+        Err(DatabaseError::ChatterNotFound { chatter_id }) if chatter_id == OTHER + 1
+    ));
+    assert!(db.select_chatter_erasures().await.unwrap().is_empty());
+}
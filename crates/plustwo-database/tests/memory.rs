@@ -0,0 +1,176 @@
+//! Checks that [`MemoryStorage`] resolves conflicts like the database does, by running the same
+//! operations against both it and `SQLite`.
+
+mod common;
+
+use common::at;
+use plustwo_database::{
+    DatabaseClient, DatabaseError, DateTime, MemoryStorage, Storage, StorageTransaction,
+    Transactional as _, Uuid,
+    entities::{
+        broadcast_minute_stats, broadcasters, broadcasts, chatters, messages,
+        sea_orm_active_enums::MessageKind,
+    },
+};
+
+const BROADCASTER: i64 = 1;
+const BROADCAST: i64 = 101;
+const CHATTERS: [i64; 2] = [201, 202];
+
+fn chatter(id: i64, display_name: &str) -> chatters::Model {
+    chatters::Model {
+        id,
+        display_name: display_name.to_string(),
+    }
+}
+
+const fn vote(id: u128, chatter_id: i64, sent_at: DateTime, value: i32) -> messages::Model {
+    messages::Model {
+        id: Uuid::from_u128(id),
+        broadcast_id: BROADCAST,
+        chatter_id,
+        sent_at,
+        message_kind: if value < 0 {
+            MessageKind::MinusTwo
+        } else {
+            MessageKind::PlusTwo
+        },
+        target_chatter_id: None,
+        value,
+        suppressed: false,
+    }
+}
+
+/// Everything a scenario left behind, for comparing storages.
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    broadcaster: Option<broadcasters::Model>,
+    broadcasts: Vec<broadcasts::Model>,
+    chatters: Vec<Option<chatters::Model>>,
+    messages: Vec<messages::Model>,
+    minute_stats: Vec<broadcast_minute_stats::Model>,
+}
+
+/// Runs operations which conflict with earlier ones, like a watcher restarting mid-broadcast.
+async fn scenario(db: &impl Storage) -> Result<Snapshot, DatabaseError> {
+    let [first, second] = CHATTERS;
+
+    db.insert_broadcaster(BROADCASTER, "memory", "Memory", "old.png")
+        .await?;
+    db.insert_broadcaster(BROADCASTER, "renamed", "Renamed", "new.png")
+        .await?;
+
+    db.start_broadcast(BROADCAST, BROADCASTER, "first".into(), at(0, 0))
+        .await?;
+    db.start_broadcast(BROADCAST, BROADCASTER, "second".into(), at(0, 1))
+        .await?;
+
+    db.insert_many_chatters(&[
+        (chatter(first, "first"), at(0, 0)),
+        (chatter(second, "second"), at(0, 0)),
+        (chatter(first, "renamed"), at(0, 30)),
+    ])
+    .await?;
+    db.insert_chatter(first, "first".into(), at(0, 10)).await?;
+
+    let votes = [
+        vote(1, first, at(0, 5), 2),
+        vote(2, second, at(0, 40), -2),
+        vote(3, first, at(1, 10), 2),
+    ];
+    db.insert_many_messages(&votes).await?;
+    db.insert_message(vote(1, first, at(0, 5), -2)).await?;
+    for vote in &votes {
+        db.refresh_minute_stats(BROADCAST, vote.sent_at).await?;
+    }
+    db.update_message_vote(votes[2].id, MessageKind::W, Some(second), 2, true)
+        .await?;
+    db.refresh_minute_stats(BROADCAST, votes[2].sent_at).await?;
+
+    db.end_broadcast(BROADCASTER, at(5, 0), None).await?;
+
+    Ok(Snapshot {
+        broadcaster: db.get_broadcaster(BROADCASTER).await?,
+        broadcasts: db.select_broadcasts(Some(BROADCASTER), None, None).await?,
+        chatters: vec![db.get_chatter(first).await?, db.get_chatter(second).await?],
+        messages: db.select_messages(BROADCAST).await?,
+        minute_stats: db.select_minute_stats(BROADCAST).await?,
+    })
+}
+
+#[tokio::test]
+async fn conflicts_match_the_database() {
+    let memory = MemoryStorage::new();
+
+    assert_eq!(
+        scenario(&memory).await.unwrap(),
+        scenario(&DatabaseClient::from(common::sqlite().await))
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn missing_broadcasts_are_reported() {
+    let memory = MemoryStorage::new();
+
+    assert!(matches!(
+        memory
+            .end_broadcast(BROADCASTER, at(5, 0), Some(BROADCAST))
+            .await,
+        Err(DatabaseError::BroadcastNotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn transactions_are_rolled_back_unless_committed() {
+    let memory = MemoryStorage::new();
+
+    let tx = memory.begin().await.unwrap();
+    tx.insert_chatter(CHATTERS[0], "dropped".into(), at(0, 0))
+        .await
+        .unwrap();
+    drop(tx);
+    assert_eq!(memory.get_chatter(CHATTERS[0]).await.unwrap(), None);
+
+    let tx = memory.begin().await.unwrap();
+    tx.insert_chatter(CHATTERS[0], "committed".into(), at(0, 0))
+        .await
+        .unwrap();
+    // Changes made outside the transaction aren't lost when it's committed.
+    memory
+        .insert_chatter(CHATTERS[1], "outside".into(), at(0, 0))
+        .await
+        .unwrap();
+    StorageTransaction::commit(tx).await.unwrap();
+
+    assert_eq!(
+        memory.get_chatter(CHATTERS[0]).await.unwrap(),
+        Some(chatter(CHATTERS[0], "committed"))
+    );
+    assert_eq!(
+        memory.get_chatter(CHATTERS[1]).await.unwrap(),
+        Some(chatter(CHATTERS[1], "outside"))
+    );
+}
+
+#[tokio::test]
+async fn cached_tables_are_versioned() {
+    let memory = MemoryStorage::new();
+    let version = memory.get_table_version("excluded_chatters").await.unwrap();
+
+    memory.set_excluded_chatters(Vec::new());
+    memory.set_opted_out_chatters(Vec::new());
+    assert!(memory.get_table_version("excluded_chatters").await.unwrap() > version);
+    assert!(
+        memory
+            .get_table_version("opted_out_chatters")
+            .await
+            .unwrap()
+            > version
+    );
+    assert_eq!(
+        memory.get_table_version("vote_patterns").await.unwrap(),
+        version
+    );
+}
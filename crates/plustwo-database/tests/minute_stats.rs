@@ -0,0 +1,130 @@
+//! Checks that per-minute rollups match the votes they're counted from.
+
+mod common;
+
+use common::at;
+use plustwo_database::{
+    DateTime, Uuid,
+    entities::{broadcast_minute_stats, chatters, messages, sea_orm_active_enums::MessageKind},
+};
+
+const BROADCASTER: i64 = 9_200_000_000_001;
+const BROADCAST: i64 = 9_200_000_000_101;
+const CHATTERS: [i64; 2] = [9_200_000_000_201, 9_200_000_000_202];
+
+const fn vote(id: u128, chatter_id: i64, sent_at: DateTime, value: i32) -> messages::Model {
+    messages::Model {
+        id: Uuid::from_u128(0x9200_0000_0000_0000 + id),
+        broadcast_id: BROADCAST,
+        chatter_id,
+        sent_at,
+        message_kind: if value < 0 {
+            MessageKind::MinusTwo
+        } else {
+            MessageKind::PlusTwo
+        },
+        target_chatter_id: None,
+        value,
+        suppressed: false,
+    }
+}
+
+fn votes() -> Vec<messages::Model> {
+    let [first, second] = CHATTERS;
+
+    vec![
+        vote(1, first, at(0, 5), 2),
+        vote(2, first, at(0, 40), 3),
+        vote(3, second, at(0, 59), -2),
+        vote(4, second, at(2, 0), -1),
+        messages::Model {
+            suppressed: true,
+            ..vote(5, first, at(3, 0), 2)
+        },
+    ]
+}
+
+const fn stats(
+    minute: DateTime,
+    (plus_count, minus_count): (i64, i64),
+    (plus_value, minus_value): (i64, i64),
+    voters: i64,
+) -> broadcast_minute_stats::Model {
+    broadcast_minute_stats::Model {
+        broadcast_id: BROADCAST,
+        minute,
+        plus_count,
+        minus_count,
+        plus_value,
+        minus_value,
+        voters,
+    }
+}
+
+fn expected() -> Vec<broadcast_minute_stats::Model> {
+    vec![
+        stats(at(0, 0), (2, 1), (5, -2), 2),
+        stats(at(2, 0), (0, 1), (0, -1), 1),
+    ]
+}
+
+fn chatters() -> Vec<(chatters::Model, DateTime)> {
+    CHATTERS
+        .iter()
+        .map(|&id| {
+            let chatter = chatters::Model {
+                id,
+                display_name: format!("chatter{id}"),
+            };
+
+            (chatter, at(0, 0))
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn refreshing_and_rebuilding_agree() {
+    let Some(tx) = common::begin().await else {
+        return;
+    };
+
+    tx.insert_broadcaster(BROADCASTER, "minute_stats", "minute_stats", "")
+        .await
+        .unwrap();
+    tx.start_broadcast(BROADCAST, BROADCASTER, "rollups".into(), at(0, 0))
+        .await
+        .unwrap();
+    tx.insert_many_chatters(&chatters()).await.unwrap();
+
+    // Votes arrive one at a time when live.
+    for vote in votes() {
+        tx.insert_message(vote.clone()).await.unwrap();
+        tx.refresh_minute_stats(BROADCAST, vote.sent_at)
+            .await
+            .unwrap();
+    }
+    assert_eq!(tx.select_minute_stats(BROADCAST).await.unwrap(), expected());
+
+    tx.rebuild_minute_stats(BROADCAST).await.unwrap();
+    assert_eq!(tx.select_minute_stats(BROADCAST).await.unwrap(), expected());
+
+    // Minutes whose votes are all removed are dropped on rebuild.
+    let removed = votes()[3].id;
+    tx.delete_many_messages(&[removed]).await.unwrap();
+    tx.rebuild_minute_stats(BROADCAST).await.unwrap();
+    assert_eq!(
+        tx.select_minute_stats(BROADCAST).await.unwrap(),
+        expected()[..1]
+    );
+
+    // Changed votes are recounted on rebuild.
+    let changed = votes()[0].id;
+    tx.update_message_vote(changed, MessageKind::MinusTwo, None, -2, false)
+        .await
+        .unwrap();
+    tx.rebuild_minute_stats(BROADCAST).await.unwrap();
+    assert_eq!(
+        tx.select_minute_stats(BROADCAST).await.unwrap(),
+        [stats(at(0, 0), (1, 2), (3, -4), 2)]
+    );
+}
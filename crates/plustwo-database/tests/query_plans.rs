@@ -0,0 +1,95 @@
+//! Checks that common queries are served by indexes rather than sequential scans.
+
+mod common;
+
+use plustwo_database::entities::{broadcasts, messages};
+use sea_orm::{
+    ColumnTrait as _, ConnectionTrait as _, Database, DatabaseTransaction, DbBackend,
+    EntityTrait as _, QueryFilter as _, QueryOrder as _, QueryTrait, Statement,
+    TransactionTrait as _,
+};
+
+async fn plan(db: &DatabaseTransaction, query: impl QueryTrait) -> String {
+    let sql = query.build(DbBackend::Postgres).to_string();
+    let rows = db
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            format!("EXPLAIN {sql}"),
+        ))
+        .await
+        .expect("query should be explainable");
+
+    rows.iter()
+        .map(|row| {
+            row.try_get::<String>("", "QUERY PLAN")
+                .expect("plan should be text")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::test]
+async fn queries_use_indexes() {
+    let Some(url) = common::database_url() else {
+        return;
+    };
+    let db = Database::connect(&url)
+        .await
+        .expect("database should connect");
+    let tx = db.begin().await.expect("transaction should start");
+
+    // Tables in tests are tiny, where sequential scans are cheapest, so they're only used when
+    // there's no usable index.
+    tx.execute_unprepared("SET LOCAL enable_seqscan = off")
+        .await
+        .expect("planner settings should apply");
+
+    let corpus = [
+        (
+            // DatabaseClient::select_messages and ::broadcast_score.
+            plan(
+                &tx,
+                messages::Entity::find().filter(messages::Column::BroadcastId.eq(1)),
+            )
+            .await,
+            "idx-messages-broadcast-id-sent-at",
+        ),
+        (
+            plan(
+                &tx,
+                messages::Entity::find().filter(messages::Column::ChatterId.eq(1)),
+            )
+            .await,
+            "idx-messages-chatter-id",
+        ),
+        (
+            // DatabaseClient::select_broadcasts.
+            plan(
+                &tx,
+                broadcasts::Entity::find()
+                    .filter(broadcasts::Column::BroadcasterId.eq(1))
+                    .order_by_asc(broadcasts::Column::StartedAt),
+            )
+            .await,
+            "idx-broadcasts-broadcaster-id-started-at",
+        ),
+        (
+            // DatabaseClient::end_broadcast.
+            plan(
+                &tx,
+                broadcasts::Entity::find()
+                    .filter(broadcasts::Column::EndedAt.is_null())
+                    .filter(broadcasts::Column::BroadcasterId.eq(1)),
+            )
+            .await,
+            "idx-broadcasts-open",
+        ),
+    ];
+
+    for (plan, index) in corpus {
+        assert!(
+            plan.contains(index),
+            "expected a scan of {index:?}, got:\n{plan}"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Checks that the client works unchanged against `SQLite`.
+
+mod common;
+
+use std::num::NonZeroU32;
+
+use chrono::TimeDelta;
+use common::{at, sqlite};
+use migration::{Migrator, MigratorTrait as _};
+use plustwo_database::{
+    DatabaseClient, DateTime, Score, ScoreBucket, Uuid,
+    entities::{
+        broadcast_minute_stats, chatter_names, chatters, messages, opted_out_chatters,
+        sea_orm_active_enums::MessageKind,
+    },
+};
+use sea_orm::{ActiveModelTrait as _, ActiveValue::Set};
+
+const BROADCASTER: i64 = 1;
+const BROADCAST: i64 = 101;
+const CHATTERS: [i64; 2] = [201, 202];
+
+/// Votes are sent part way through a second, to check that timestamps keep their precision.
+fn sent(minute: u32, second: u32) -> DateTime {
+    common::at(minute, second) + TimeDelta::milliseconds(250)
+}
+
+const fn vote(id: u128, chatter_id: i64, sent_at: DateTime, value: i32) -> messages::Model {
+    messages::Model {
+        id: Uuid::from_u128(id),
+        broadcast_id: BROADCAST,
+        chatter_id,
+        sent_at,
+        message_kind: if value < 0 {
+            MessageKind::MinusTwo
+        } else {
+            MessageKind::PlusTwo
+        },
+        target_chatter_id: None,
+        value,
+        suppressed: false,
+    }
+}
+
+/// Inserts a broadcaster with a broadcast, its chatters and their votes.
+async fn fixtures() -> DatabaseClient {
+    let db = DatabaseClient::from(sqlite().await);
+
+    db.insert_broadcaster(BROADCASTER, "sqlite", "SQLite", "")
+        .await
+        .unwrap();
+    db.start_broadcast(BROADCAST, BROADCASTER, "portable".into(), at(0, 0))
+        .await
+        .unwrap();
+
+    let chatters = CHATTERS.map(|id| {
+        let chatter = chatters::Model {
+            id,
+            display_name: format!("chatter{id}"),
+        };
+
+        (chatter, at(0, 0))
+    });
+    db.insert_many_chatters(&chatters).await.unwrap();
+
+    let [first, second] = CHATTERS;
+    for vote in [
+        vote(1, first, sent(0, 5), 2),
+        vote(2, first, sent(0, 40), 3),
+        vote(3, second, sent(1, 10), -2),
+    ] {
+        db.insert_message(vote.clone()).await.unwrap();
+        db.refresh_minute_stats(BROADCAST, vote.sent_at)
+            .await
+            .unwrap();
+    }
+
+    db
+}
+
+#[tokio::test]
+async fn migrations_roll_back() {
+    let db = sqlite().await;
+
+    Migrator::down(&db, None)
+        .await
+        .expect("migrations should roll back");
+    Migrator::up(&db, None)
+        .await
+        .expect("migrations should reapply");
+}
+
+#[tokio::test]
+async fn vote_targets_can_be_changed() {
+    let db = fixtures().await;
+    let vote = db.select_messages(BROADCAST).await.unwrap()[0].clone();
+
+    db.update_message_vote(
+        vote.id,
+        vote.message_kind.clone(),
+        Some(CHATTERS[1]),
+        vote.value,
+        vote.suppressed,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        db.select_messages(BROADCAST)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|m| m.id == vote.id)
+            .unwrap(),
+        messages::Model {
+            target_chatter_id: Some(CHATTERS[1]),
+            ..vote
+        }
+    );
+}
+
+#[tokio::test]
+async fn votes_are_stored_and_counted() {
+    let db = fixtures().await;
+
+    let mut votes = db.select_messages(BROADCAST).await.unwrap();
+    votes.sort_by_key(|vote| vote.sent_at);
+    assert_eq!(votes[0], vote(1, CHATTERS[0], sent(0, 5), 2));
+
+    db.update_message_vote(votes[1].id, MessageKind::W, None, 2, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        db.broadcast_score(BROADCAST, None).await.unwrap(),
+        Score {
+            plus: 4,
+            minus: -2,
+            net: 2,
+            votes: 3,
+            voters: 2,
+        }
+    );
+    assert_eq!(
+        db.score_series(BROADCAST, TimeDelta::minutes(1), None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|bucket| bucket.start)
+            .collect::<Vec<_>>(),
+        [at(0, 0), at(1, 0)]
+    );
+    // Clamps too large for a vote's value leave every vote as it is.
+    assert_eq!(
+        db.broadcast_score(BROADCAST, Some(NonZeroU32::MAX))
+            .await
+            .unwrap(),
+        db.broadcast_score(BROADCAST, None).await.unwrap()
+    );
+    assert_eq!(
+        db.chatter_scores(BROADCAST, Some(NonZeroU32::MIN))
+            .await
+            .unwrap()[0]
+            .score,
+        Score {
+            plus: 2,
+            minus: 0,
+            net: 2,
+            votes: 2,
+            voters: 1,
+        }
+    );
+    assert!(matches!(
+        db.score_series(BROADCAST, TimeDelta::hours(1), None)
+            .await
+            .unwrap()
+            .as_slice(),
+        [ScoreBucket { start, .. }] if *start == at(0, 0)
+    ));
+
+    db.end_broadcast(BROADCASTER, at(5, 0), None).await.unwrap();
+    assert_eq!(
+        db.select_broadcasts(Some(BROADCASTER), Some(at(1, 0)), None)
+            .await
+            .unwrap()[0]
+            .ended_at,
+        Some(at(5, 0))
+    );
+}
+
+#[tokio::test]
+async fn minute_stats_are_rolled_up() {
+    let db = fixtures().await;
+
+    let stats = |minute, (plus_count, minus_count), (plus_value, minus_value)| {
+        broadcast_minute_stats::Model {
+            broadcast_id: BROADCAST,
+            minute,
+            plus_count,
+            minus_count,
+            plus_value,
+            minus_value,
+            voters: 1,
+        }
+    };
+    let expected = [
+        stats(at(0, 0), (2, 0), (5, 0)),
+        stats(at(1, 0), (0, 1), (0, -2)),
+    ];
+    assert_eq!(db.select_minute_stats(BROADCAST).await.unwrap(), expected);
+
+    db.delete_many_messages(&[Uuid::from_u128(3)])
+        .await
+        .unwrap();
+    db.rebuild_minute_stats(BROADCAST).await.unwrap();
+    assert_eq!(
+        db.select_minute_stats(BROADCAST).await.unwrap(),
+        expected[..1]
+    );
+}
+
+#[tokio::test]
+async fn chatter_names_are_kept() {
+    let db = fixtures().await;
+    let chatter = CHATTERS[0];
+
+    db.insert_chatter(chatter, "renamed".into(), at(10, 0))
+        .await
+        .unwrap();
+    db.insert_chatter(chatter, format!("chatter{chatter}"), sent(0, 5))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        db.get_chatter(chatter).await.unwrap().unwrap().display_name,
+        "renamed"
+    );
+    assert_eq!(
+        db.select_chatter_names(chatter).await.unwrap(),
+        [
+            chatter_names::Model {
+                chatter_id: chatter,
+                display_name: format!("chatter{chatter}"),
+                first_seen_at: at(0, 0),
+                last_seen_at: sent(0, 5),
+            },
+            chatter_names::Model {
+                chatter_id: chatter,
+                display_name: "renamed".into(),
+                first_seen_at: at(10, 0),
+                last_seen_at: at(10, 0),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn cached_tables_are_seeded_and_versioned() {
+    let db = DatabaseClient::from(sqlite().await);
+
+    assert_eq!(db.select_excluded_chatters().await.unwrap().len(), 2);
+    assert!(db.select_vote_patterns().await.unwrap().is_empty());
+    assert!(db.get_table_version("excluded_chatters").await.unwrap() > 0);
+    assert!(db.get_table_version("vote_patterns").await.unwrap() > 0);
+}
+
+#[tokio::test]
+async fn opt_outs_are_versioned() {
+    let conn = sqlite().await;
+    opted_out_chatters::ActiveModel {
+        chatter_id: Set(CHATTERS[0]),
+        ..Default::default()
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    let db = DatabaseClient::from(conn);
+
+    let opt_outs = db.select_opted_out_chatters().await.unwrap();
+    assert_eq!(opt_outs[0].chatter_id, CHATTERS[0]);
+    assert!(db.get_table_version("opted_out_chatters").await.unwrap() > 0);
+}
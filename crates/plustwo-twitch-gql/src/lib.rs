@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use shared::{QueryConnection, QueryResponse, video::TwitchVideo};
+use shared::{QueryConnection, QueryResponse, badge::ChatterRoles, video::TwitchVideo};
 use uuid::Uuid;
 
 /// This is a a client ID I just grabbed from some open source project. The default unauthorized
@@ -12,7 +12,13 @@ pub mod shared;
 pub struct TwitchGqlClient {
     client: reqwest::Client,
 }
+impl Default for TwitchGqlClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 impl TwitchGqlClient {
+    #[must_use]
     pub fn new() -> Self {
         let client = reqwest::Client::builder()
             .default_headers({
@@ -98,6 +104,17 @@ impl TwitchGqlClient {
                                     message {{
                                         fragments {{
                                             text
+                                            mention {{
+                                                id
+                                                displayName
+                                            }}
+                                            emote {{
+                                                emoteID
+                                            }}
+                                        }}
+                                        userBadges {{
+                                            setID
+                                            version
                                         }}
                                     }}
                                 }}
@@ -121,28 +138,27 @@ impl TwitchGqlClient {
             .client
             .post("https://gql.twitch.tv/gql")
             .json(&GenericQuery {
-                query: format!(
-                    r#"
-                query {{
-                    user(login: "{login}") {{
-                        id
-                        profileImageURL(width: 300)
-                        broadcastSettings {{
-                            title
-                        }}
-                        stream {{
-                            id
-                            archiveVideo {{
-                                createdAt
-                                id
-                                title
-                                lengthSeconds
-                            }}
-                        }}
-                    }}
-                }}
-                "#,
-                ),
+                query: user_and_stream_query(&format!(r#"login: "{login}""#)),
+            })
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        Ok(res.data.user)
+    }
+
+    /// Looks up a user by ID, which unlike their login never changes. Returns `None` if the user
+    /// no longer exists.
+    pub async fn get_stream_by_id(
+        &self,
+        id: &str,
+    ) -> reqwest::Result<Option<UserAndStreamByLogin>> {
+        let res: QueryResponse<UserQueryResponse<Option<UserAndStreamByLogin>>> = self
+            .client
+            .post("https://gql.twitch.tv/gql")
+            .json(&GenericQuery {
+                query: user_and_stream_query(&format!(r#"id: "{id}""#)),
             })
             .send()
             .await?
@@ -153,6 +169,34 @@ impl TwitchGqlClient {
     }
 }
 
+/// Builds a query for a user and their current stream, finding the user by `selector`.
+fn user_and_stream_query(selector: &str) -> String {
+    format!(
+        r"
+        query {{
+            user({selector}) {{
+                id
+                login
+                displayName
+                profileImageURL(width: 300)
+                broadcastSettings {{
+                    title
+                }}
+                stream {{
+                    id
+                    archiveVideo {{
+                        createdAt
+                        id
+                        title
+                        lengthSeconds
+                    }}
+                }}
+            }}
+        }}
+        "
+    )
+}
+
 pub async fn collect_from_cursor<T, F>(mut f: F) -> reqwest::Result<Vec<T>>
 where
     F: AsyncFnMut(Option<String>, usize, &Vec<T>) -> reqwest::Result<QueryConnection<T>>,
@@ -235,12 +279,48 @@ pub struct CommentsByVideoAndCursorUser {
 #[serde(rename_all = "camelCase")]
 pub struct CommentsByVideoAndCursorMessage {
     pub fragments: Vec<CommentsByVideoAndCursorFragment>,
+    #[serde(default)]
+    pub user_badges: Vec<CommentsByVideoAndCursorBadge>,
+}
+impl CommentsByVideoAndCursorMessage {
+    /// Parses the chatter's roles out of the badges they were displaying.
+    #[must_use]
+    pub fn roles(&self) -> ChatterRoles {
+        ChatterRoles::from_badges(
+            self.user_badges
+                .iter()
+                .map(|b| (b.set_id.as_str(), b.version.as_str())),
+        )
+    }
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommentsByVideoAndCursorFragment {
     pub text: String,
+    pub mention: Option<CommentsByVideoAndCursorMention>,
+    pub emote: Option<CommentsByVideoAndCursorEmote>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentsByVideoAndCursorMention {
+    pub id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentsByVideoAndCursorEmote {
+    #[serde(rename = "emoteID")]
+    pub emote_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentsByVideoAndCursorBadge {
+    #[serde(rename = "setID")]
+    pub set_id: String,
+    pub version: String,
 }
 
 //
@@ -249,6 +329,8 @@ pub struct CommentsByVideoAndCursorFragment {
 #[serde(rename_all = "camelCase")]
 pub struct UserAndStreamByLogin {
     pub id: String,
+    pub login: String,
+    pub display_name: String,
     #[serde(rename = "profileImageURL")]
     pub profile_image_url: String,
     pub broadcast_settings: UserAndStreamByLoginBroadcastSettings,
@@ -0,0 +1,37 @@
+/// The roles a chatter had when they sent a message, as shown by their badges.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChatterRoles {
+    pub subscriber: bool,
+    pub moderator: bool,
+    pub vip: bool,
+    pub founder: bool,
+    /// The number of months the chatter has been subscribed for. Subscriber badges only change
+    /// at certain milestones, so unless a more exact value is known this is a lower bound.
+    pub subscriber_months: Option<i32>,
+}
+impl ChatterRoles {
+    /// Parses roles from a list of `(set_id, version)` badge pairs.
+    pub fn from_badges<'a>(badges: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut roles = Self::default();
+
+        for (set_id, version) in badges {
+            match set_id {
+                "subscriber" => {
+                    roles.subscriber = true;
+                    // Tier 2 and 3 badges are offset by 2000 and 3000 respectively.
+                    roles.subscriber_months = version.parse::<i32>().ok().map(|v| v % 1000);
+                }
+                "founder" => {
+                    roles.subscriber = true;
+                    roles.founder = true;
+                }
+                "moderator" => roles.moderator = true,
+                "vip" => roles.vip = true,
+                _ => {}
+            }
+        }
+
+        roles
+    }
+}
@@ -1,5 +1,6 @@
 use serde::Deserialize;
 
+pub mod badge;
 pub mod video;
 
 #[derive(Debug, Deserialize)]